@@ -0,0 +1,77 @@
+#![warn(clippy::all)]
+
+// A benchmark of the scanner on generated programs of growing size, to check that scanning time grows linearly with the size of the source.
+// Run it with `cargo bench --bench scanner`. It fails if doubling the source more than triples the time per byte.
+
+use std::process;
+use std::time::{Duration, Instant};
+
+// A class body with a bit of everything in it: keywords, identifiers, constants, special symbols, a comment and a multi-byte character.
+const BODY: &str = "protected object {
+val i, j, k : int;
+if (@ x 25) case i = j + k * 5 => print (i); # naïve
+else in (i, j, k);
+def x (y, w) { y <= w; };
+while (not ( true or false)) return (47.5 * (x + 25));
+}
+";
+
+// The sizes of the generated programs, in bytes.
+const SIZES: [usize; 5] = [1 << 18, 1 << 19, 1 << 20, 1 << 21, 1 << 22];
+
+// How many times to scan each program. The fastest run is the one that counts.
+const RUNS: usize = 3;
+
+fn main() {
+    println!(
+        "{0: <15} | {1: <15} | {2: <15} | {3: <}",
+        "Bytes", "Tokens", "Time (ms)", "ns/byte"
+    );
+
+    let mut ns_per_byte: Vec<f64> = Vec::new();
+    for size in SIZES {
+        let source = generate(size);
+
+        let mut fastest = Duration::MAX;
+        let mut tokens = 0;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            let result = parser::scan(&source);
+            fastest = fastest.min(start.elapsed());
+            tokens = result.tokens().len();
+        }
+
+        let per_byte = fastest.as_nanos() as f64 / source.len() as f64;
+        ns_per_byte.push(per_byte);
+        println!(
+            "{0: <15} | {1: <15} | {2: <15} | {3: <.2}",
+            source.len(),
+            tokens,
+            fastest.as_millis(),
+            per_byte
+        );
+    }
+
+    // With a linear scanner, the time per byte stays about the same as the source grows. With a quadratic one, it doubles each time.
+    for (i, pair) in ns_per_byte.windows(2).enumerate() {
+        if pair[1] > pair[0] * 3.0 {
+            println!(
+                "Scanning is not linear: {} bytes took {:.2} ns/byte, but {} bytes took {:.2} ns/byte.",
+                SIZES[i], pair[0], SIZES[i + 1], pair[1]
+            );
+            process::exit(1);
+        }
+    }
+    println!("Scanning time grows linearly with the size of the source.");
+}
+
+// Generate a program of at least the given size.
+fn generate(size: usize) -> String {
+    let mut source = String::from("package a;\nimport b.c;\n");
+    while source.len() < size {
+        source.push_str(BODY);
+    }
+    source.push_str("$\n");
+
+    source
+}
@@ -0,0 +1,762 @@
+#![warn(clippy::all)]
+
+// The abstract syntax tree for a Simple Scala program.
+// The tree is built from the leftmost derivation that the PDA produces: the rules it applied (in order) and the terminals it matched (in order).
+// Because an LL(1) parse visits the derivation in preorder, we can walk both lists once, from the front, and never need to backtrack.
+
+use crate::bookkeeper::Token;
+use crate::diagnostic::Span;
+
+// The root of the tree. A program is a list of packages, a list of imports and a list of class/object bodies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    pub packages: Vec<Identifier>,
+    pub imports: Vec<Identifier>,
+    pub bodies: Vec<Body>,
+}
+
+// An identifier, along with where it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    pub line_number: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+// A numeric constant, stored as it was written in the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constant {
+    pub value: String,
+    pub line_number: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+// The modifiers that may precede a class or an object.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Modifier {
+    Abstract,
+    Final,
+    Sealed,
+    Private,
+    Protected,
+}
+
+// Whether a body was declared as a class or as an object.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BodyKind {
+    Class,
+    Object,
+}
+
+// A class or object body, e.g. `abstract class { ... }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Body {
+    pub modifier: Modifier,
+    pub kind: BodyKind,
+    pub block: Block,
+    pub line_number: usize,
+}
+
+// A list of statements between curly brackets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+}
+
+// The types that a `val` declaration may use.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Type {
+    Int,
+    Real,
+    Bool,
+}
+
+// This tells the program how to println a Type in a nice way.
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            Type::Int => "int",
+            Type::Real => "real",
+            Type::Bool => "bool",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    // val a, b, c : real
+    Val {
+        names: Vec<Identifier>,
+        var_type: Type,
+    },
+    // def x (y, w) { ... }
+    Def {
+        name: Identifier,
+        params: Vec<Identifier>,
+        body: Block,
+    },
+    // y <= w
+    Assign {
+        target: Identifier,
+        value: Expr,
+    },
+    // if (expr) stmt; else stmt
+    If {
+        condition: Expr,
+        then_branch: Box<Statement>,
+        else_branch: Box<Statement>,
+    },
+    // while (expr) stmt
+    While {
+        condition: Expr,
+        body: Box<Statement>,
+    },
+    // case i = expr => stmt
+    Case {
+        name: Identifier,
+        value: Expr,
+        body: Box<Statement>,
+    },
+    // in (a, b, c)
+    In {
+        names: Vec<Identifier>,
+    },
+    // print (a, b, c)
+    Print {
+        names: Vec<Identifier>,
+    },
+    // return (expr)
+    Return {
+        value: Expr,
+    },
+    // { stmts }
+    Block(Block),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Arith(ArithExpr),
+    Bool(BoolExpr),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArithOp {
+    Add,
+    Multiply,
+}
+
+// The grammar has no precedence levels, so `a + b * c` is `a + (b * c)` and `a * b + c` is `a * (b + c)`.
+// Both operators are right associative.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArithExpr {
+    Identifier(Identifier),
+    Constant(Constant),
+    Binary {
+        op: ArithOp,
+        left: Box<ArithExpr>,
+        right: Box<ArithExpr>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BoolOp {
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BoolExpr {
+    Literal(bool),
+    Not(Box<BoolExpr>),
+    // @ x 25
+    Compare {
+        left: ArithExpr,
+        right: ArithExpr,
+    },
+    Binary {
+        op: BoolOp,
+        left: Box<BoolExpr>,
+        right: Box<BoolExpr>,
+    },
+}
+
+// Build the tree for an accepted program, given the rules that the PDA applied and the terminals that it matched, both in order.
+// This should only be called on the output of a successful parse; a derivation that does not fit the grammar is a bug, so we panic.
+pub fn build(derivation: &[u16], matched: &[Token]) -> Program {
+    let mut builder = Builder {
+        rules: derivation.iter(),
+        tokens: matched.iter(),
+    };
+
+    builder.scala()
+}
+
+// Walks the derivation and the matched terminals side by side. There is one method per nonterminal in the grammar.
+struct Builder<'a> {
+    rules: std::slice::Iter<'a, u16>,
+    tokens: std::slice::Iter<'a, Token>,
+}
+
+impl<'a> Builder<'a> {
+    // The next rule in the derivation.
+    fn rule(&mut self) -> u16 {
+        match self.rules.next() {
+            Some(rule) => *rule,
+            None => panic!("The derivation ended before the tree was complete!"),
+        }
+    }
+
+    // The next matched terminal.
+    fn token(&mut self) -> &'a Token {
+        match self.tokens.next() {
+            Some(tkn) => tkn,
+            None => panic!("Ran out of matched tokens before the tree was complete!"),
+        }
+    }
+
+    fn identifier(&mut self) -> Identifier {
+        let tkn = self.token();
+
+        Identifier {
+            name: tkn.token.clone(),
+            line_number: tkn.line_number,
+            column: tkn.column,
+            span: tkn.span,
+        }
+    }
+
+    // <scala> -> <packages> <imports> <scala-body>
+    fn scala(&mut self) -> Program {
+        self.rule();
+        let packages = self.packages();
+        let imports = self.imports();
+        let bodies = self.scala_body();
+
+        Program {
+            packages,
+            imports,
+            bodies,
+        }
+    }
+
+    // <packages> -> package [id] ; <packages> | epsilon
+    fn packages(&mut self) -> Vec<Identifier> {
+        let mut packages = Vec::new();
+        while self.rule() == 2 {
+            self.token();
+            packages.push(self.identifier());
+            self.token();
+        }
+
+        packages
+    }
+
+    // <imports> -> import [id] ; <imports> | epsilon
+    fn imports(&mut self) -> Vec<Identifier> {
+        let mut imports = Vec::new();
+        while self.rule() == 4 {
+            self.token();
+            imports.push(self.identifier());
+            self.token();
+        }
+
+        imports
+    }
+
+    // <scala-body> -> <subbody> <scala-body> | epsilon
+    fn scala_body(&mut self) -> Vec<Body> {
+        let mut bodies = Vec::new();
+        while self.rule() == 6 {
+            bodies.push(self.subbody());
+        }
+
+        bodies
+    }
+
+    // <subbody> -> <modifier> <subbody-tail>
+    // <subbody-tail> -> <tail-type> <block>
+    fn subbody(&mut self) -> Body {
+        self.rule();
+        let modifier = match self.rule() {
+            9 => Modifier::Abstract,
+            10 => Modifier::Final,
+            11 => Modifier::Sealed,
+            12 => Modifier::Private,
+            13 => Modifier::Protected,
+            rule => panic!("Rule {} does not expand <modifier>!", rule),
+        };
+        let line_number = self.token().line_number;
+
+        self.rule();
+        let kind = match self.rule() {
+            15 => BodyKind::Class,
+            16 => BodyKind::Object,
+            rule => panic!("Rule {} does not expand <tail-type>!", rule),
+        };
+        self.token();
+
+        Body {
+            modifier,
+            kind,
+            block: self.block(),
+            line_number,
+        }
+    }
+
+    // <block> -> { <stmts> }
+    fn block(&mut self) -> Block {
+        self.rule();
+        self.token();
+        let statements = self.stmts();
+        self.token();
+
+        Block { statements }
+    }
+
+    // <stmts> -> <stmt> ; <stmts> | epsilon
+    fn stmts(&mut self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        while self.rule() == 18 {
+            statements.push(self.stmt());
+            self.token();
+        }
+
+        statements
+    }
+
+    fn stmt(&mut self) -> Statement {
+        match self.rule() {
+            20 => self.dcl(),
+            21 => self.asmt(),
+            22 => self.if_stmt(),
+            23 => self.while_stmt(),
+            24 => self.case(),
+            25 => self.in_stmt(),
+            26 => self.out(),
+            27 => self.return_stmt(),
+            28 => Statement::Block(self.block()),
+            rule => panic!("Rule {} does not expand <stmt>!", rule),
+        }
+    }
+
+    // <dcl> -> val <dcl-tail> | def [id] ( <ids> ) <block>
+    // <dcl-tail> -> <ids> : <type>
+    fn dcl(&mut self) -> Statement {
+        match self.rule() {
+            29 => {
+                self.token();
+                self.rule();
+                let names = self.ids();
+                self.token();
+                let var_type = self.var_type();
+
+                Statement::Val { names, var_type }
+            }
+            30 => {
+                self.token();
+                let name = self.identifier();
+                self.token();
+                let params = self.ids();
+                self.token();
+                let body = self.block();
+
+                Statement::Def { name, params, body }
+            }
+            rule => panic!("Rule {} does not expand <dcl>!", rule),
+        }
+    }
+
+    // <ids> -> [id] <more-ids>
+    // <more-ids> -> , [id] <more-ids> | epsilon
+    fn ids(&mut self) -> Vec<Identifier> {
+        self.rule();
+        let mut ids = vec![self.identifier()];
+        while self.rule() == 33 {
+            self.token();
+            ids.push(self.identifier());
+        }
+
+        ids
+    }
+
+    // <type> -> int | real | bool
+    fn var_type(&mut self) -> Type {
+        let var_type = match self.rule() {
+            35 => Type::Int,
+            36 => Type::Real,
+            37 => Type::Bool,
+            rule => panic!("Rule {} does not expand <type>!", rule),
+        };
+        self.token();
+
+        var_type
+    }
+
+    // <asmt> -> [id] <= <expr>
+    fn asmt(&mut self) -> Statement {
+        self.rule();
+        let target = self.identifier();
+        self.token();
+        let value = self.expr();
+
+        Statement::Assign { target, value }
+    }
+
+    // <if> -> if ( <expr> ) <stmt> ; else <stmt>
+    fn if_stmt(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        self.token();
+        let condition = self.expr();
+        self.token();
+        let then_branch = Box::new(self.stmt());
+        self.token();
+        self.token();
+        let else_branch = Box::new(self.stmt());
+
+        Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+        }
+    }
+
+    // <while> -> while ( <expr> ) <stmt>
+    fn while_stmt(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        self.token();
+        let condition = self.expr();
+        self.token();
+        let body = Box::new(self.stmt());
+
+        Statement::While { condition, body }
+    }
+
+    // <case> -> case [id] = <expr> => <stmt>
+    fn case(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        let name = self.identifier();
+        self.token();
+        let value = self.expr();
+        self.token();
+        let body = Box::new(self.stmt());
+
+        Statement::Case { name, value, body }
+    }
+
+    // <in> -> in ( <ids> )
+    fn in_stmt(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        self.token();
+        let names = self.ids();
+        self.token();
+
+        Statement::In { names }
+    }
+
+    // <out> -> print ( <ids> )
+    fn out(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        self.token();
+        let names = self.ids();
+        self.token();
+
+        Statement::Print { names }
+    }
+
+    // <return> -> return ( <expr> )
+    fn return_stmt(&mut self) -> Statement {
+        self.rule();
+        self.token();
+        self.token();
+        let value = self.expr();
+        self.token();
+
+        Statement::Return { value }
+    }
+
+    // <expr> -> <arith-expr> | <bool-exp>
+    fn expr(&mut self) -> Expr {
+        match self.rule() {
+            45 => Expr::Arith(self.arith_expr()),
+            46 => Expr::Bool(self.bool_exp()),
+            rule => panic!("Rule {} does not expand <expr>!", rule),
+        }
+    }
+
+    // <arith-expr> -> [id] <arith> | [const] <arith> | ( <arith-expr> ) <arith>
+    fn arith_expr(&mut self) -> ArithExpr {
+        let left = match self.rule() {
+            47 => ArithExpr::Identifier(self.identifier()),
+            48 => {
+                let tkn = self.token();
+                ArithExpr::Constant(Constant {
+                    value: tkn.token.clone(),
+                    line_number: tkn.line_number,
+                    column: tkn.column,
+                    span: tkn.span,
+                })
+            }
+            49 => {
+                self.token();
+                let inner = self.arith_expr();
+                self.token();
+                inner
+            }
+            rule => panic!("Rule {} does not expand <arith-expr>!", rule),
+        };
+
+        self.arith(left)
+    }
+
+    // <arith> -> + <arith-expr> | * <arith-expr> | epsilon
+    fn arith(&mut self, left: ArithExpr) -> ArithExpr {
+        let op = match self.rule() {
+            50 => ArithOp::Add,
+            51 => ArithOp::Multiply,
+            52 => return left,
+            rule => panic!("Rule {} does not expand <arith>!", rule),
+        };
+        self.token();
+
+        ArithExpr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(self.arith_expr()),
+        }
+    }
+
+    // <bool-exp> -> not ( <bool-exp> ) <bool> | true <bool> | false <bool> | @ <arith-expr> <arith-expr>
+    fn bool_exp(&mut self) -> BoolExpr {
+        match self.rule() {
+            53 => {
+                self.token();
+                self.token();
+                let inner = self.bool_exp();
+                self.token();
+                self.bool_tail(BoolExpr::Not(Box::new(inner)))
+            }
+            54 => {
+                self.token();
+                self.bool_tail(BoolExpr::Literal(true))
+            }
+            55 => {
+                self.token();
+                self.bool_tail(BoolExpr::Literal(false))
+            }
+            56 => {
+                self.token();
+                let left = self.arith_expr();
+                let right = self.arith_expr();
+                BoolExpr::Compare { left, right }
+            }
+            rule => panic!("Rule {} does not expand <bool-exp>!", rule),
+        }
+    }
+
+    // <bool> -> and <bool-exp> | or <bool-exp> | epsilon
+    fn bool_tail(&mut self, left: BoolExpr) -> BoolExpr {
+        let op = match self.rule() {
+            57 => BoolOp::And,
+            58 => BoolOp::Or,
+            59 => return left,
+            rule => panic!("Rule {} does not expand <bool>!", rule),
+        };
+        self.token();
+
+        BoolExpr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(self.bool_exp()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod ast_tests {
+    use crate::ast::*;
+    use crate::bookkeeper::Bookkeeper;
+    use crate::parser::Parser;
+
+    fn build_ast(src: &str) -> Option<Program> {
+        let mut p = Parser::new(src.to_string(), Bookkeeper::new());
+
+        p.parse_program()
+    }
+
+    // The identifier found at the given line and column of the source.
+    fn id(src: &str, name: &str, line_number: usize, column: usize) -> Identifier {
+        let start = offset(src, line_number, column);
+        assert_eq!(&src[start..start + name.len()], name);
+
+        Identifier {
+            name: name.to_string(),
+            line_number,
+            column,
+            span: Span::new(start, start + name.len()),
+        }
+    }
+
+    fn constant(src: &str, value: &str, line_number: usize, column: usize) -> Constant {
+        let start = offset(src, line_number, column);
+        assert_eq!(&src[start..start + value.len()], value);
+
+        Constant {
+            value: value.to_string(),
+            line_number,
+            column,
+            span: Span::new(start, start + value.len()),
+        }
+    }
+
+    // The byte offset of a line and column, both counting from 1. The sources here are all ASCII.
+    fn offset(src: &str, line_number: usize, column: usize) -> usize {
+        let line_start: usize = src
+            .split_inclusive('\n')
+            .take(line_number - 1)
+            .map(|line| line.len())
+            .sum();
+
+        line_start + column - 1
+    }
+
+    #[test]
+    fn test_rejected_program_has_no_tree() {
+        assert!(build_ast("abstract class a {}\n$\n").is_none());
+    }
+
+    #[test]
+    fn test_packages_and_imports() {
+        let src = "package a;\nimport b.c;\n$\n";
+        let program = build_ast(src).unwrap();
+
+        assert_eq!(program.packages, vec![id(src, "a", 1, 9)]);
+        assert_eq!(program.imports, vec![id(src, "b.c", 2, 8)]);
+        assert!(program.bodies.is_empty());
+    }
+
+    #[test]
+    fn test_body_with_declarations() {
+        let src = "private object {\nval a, b : int;\ndef f (x) { x <= 1; };\n}\n$\n";
+        let program = build_ast(src).unwrap();
+        let body = &program.bodies[0];
+
+        assert_eq!(body.modifier, Modifier::Private);
+        assert_eq!(body.kind, BodyKind::Object);
+        assert_eq!(
+            body.block.statements[0],
+            Statement::Val {
+                names: vec![id(src, "a", 2, 5), id(src, "b", 2, 8)],
+                var_type: Type::Int,
+            }
+        );
+
+        let expected_def = Statement::Def {
+            name: id(src, "f", 3, 5),
+            params: vec![id(src, "x", 3, 8)],
+            body: Block {
+                statements: vec![Statement::Assign {
+                    target: id(src, "x", 3, 13),
+                    value: Expr::Arith(ArithExpr::Constant(constant(src, "1", 3, 18))),
+                }],
+            },
+        };
+        assert_eq!(body.block.statements[1], expected_def);
+    }
+
+    #[test]
+    fn test_arithmetic_is_right_associative() {
+        let src = "final class { x <= a * b + c; }\n$\n";
+        let program = build_ast(src).unwrap();
+
+        let expected = Expr::Arith(ArithExpr::Binary {
+            op: ArithOp::Multiply,
+            left: Box::new(ArithExpr::Identifier(id(src, "a", 1, 20))),
+            right: Box::new(ArithExpr::Binary {
+                op: ArithOp::Add,
+                left: Box::new(ArithExpr::Identifier(id(src, "b", 1, 24))),
+                right: Box::new(ArithExpr::Identifier(id(src, "c", 1, 28))),
+            }),
+        });
+
+        match &program.bodies[0].block.statements[0] {
+            Statement::Assign { value, .. } => assert_eq!(value, &expected),
+            other => panic!("Expected an assignment, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_boolean_expression() {
+        let src = "sealed class { return (not (true or @ x 5) and false); }\n$\n";
+        let program = build_ast(src).unwrap();
+
+        let expected = Expr::Bool(BoolExpr::Binary {
+            op: BoolOp::And,
+            left: Box::new(BoolExpr::Not(Box::new(BoolExpr::Binary {
+                op: BoolOp::Or,
+                left: Box::new(BoolExpr::Literal(true)),
+                right: Box::new(BoolExpr::Compare {
+                    left: ArithExpr::Identifier(id(src, "x", 1, 39)),
+                    right: ArithExpr::Constant(constant(src, "5", 1, 41)),
+                }),
+            }))),
+            right: Box::new(BoolExpr::Literal(false)),
+        });
+
+        assert_eq!(
+            program.bodies[0].block.statements[0],
+            Statement::Return { value: expected }
+        );
+    }
+
+    #[test]
+    fn test_dr_kim_source_program() {
+        let src = "package a;
+        package b;
+        import a.xyz; import b.c...67;
+        abstract class {
+        val a, b, c : real;
+        def x (y, w) { y <= w; };
+        while (not ( true or false)) return (47 * (x + 25)); }
+        protected object {
+        val i, j, k : int;
+        if (@ x 25) case i = j + k * 5 => print (i);
+        else in (i, j, k);
+        }
+        private class {
+        val tt, ff: bool;
+        return (not (true or @ x 5) and false);
+        }
+        $
+        ";
+        let program = build_ast(src).unwrap();
+
+        assert_eq!(program.packages.len(), 2);
+        assert_eq!(program.imports[1], id(src, "b.c...67", 3, 30));
+        assert_eq!(program.bodies.len(), 3);
+        assert_eq!(program.bodies[1].modifier, Modifier::Protected);
+        assert_eq!(program.bodies[1].line_number, 8);
+
+        match &program.bodies[1].block.statements[1] {
+            Statement::If { else_branch, .. } => assert_eq!(
+                **else_branch,
+                Statement::In {
+                    names: vec![
+                        id(src, "i", 11, 18),
+                        id(src, "j", 11, 21),
+                        id(src, "k", 11, 24)
+                    ]
+                }
+            ),
+            other => panic!("Expected an if statement, found {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,469 @@
+#![warn(clippy::all)]
+
+// Loading a grammar from a BNF file, so that dialects of Simple Scala can be tried out without recompiling.
+// See grammars/simple_scala.bnf for the syntax. Terminals are named as in the parse output, because the scanner is the same whatever the grammar.
+// Nonterminals with the same names as ours keep their codes, and every other nonterminal is given a new code from 70 upwards.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::grammar::{Grammar, Production};
+use crate::symbol::{NonTerminal, Symbol, Terminal};
+
+// Something wrong with a grammar file, and the line that it is on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BnfError {
+    pub(crate) line_number: usize,
+    pub(crate) message: String,
+}
+
+impl BnfError {
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+// This tells the program how to println a BnfError in a nice way.
+impl std::fmt::Display for BnfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line_number, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Lexeme {
+    NonTerminal(String),
+    Terminal(String),
+    Epsilon,
+    Defines, // ::=
+    Bar,
+    Open(char),
+    Close(char),
+}
+
+// Parse the text of a grammar file.
+pub fn parse(text: &str) -> Result<Grammar, BnfError> {
+    let lexemes = tokenize(text)?;
+    let mut loader = Loader {
+        lexemes,
+        index: 0,
+        codes: HashMap::new(),
+        names: BTreeMap::new(),
+        next_code: NonTerminal::FIRST_NEW_CODE,
+        written: Vec::new(),
+        generated: Vec::new(),
+        used: BTreeMap::new(),
+        generated_count: 0,
+    };
+
+    loader.grammar()
+}
+
+// Split the text of a grammar file into lexemes, along with the line that each is on.
+fn tokenize(text: &str) -> Result<Vec<(Lexeme, usize)>, BnfError> {
+    let mut lexemes = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            let lexeme = match c {
+                c if c.is_whitespace() => continue,
+                // A comment runs to the end of the line.
+                '#' => break,
+                '|' => Lexeme::Bar,
+                '[' | '{' | '(' => Lexeme::Open(c),
+                ']' | '}' | ')' => Lexeme::Close(c),
+                'ε' => Lexeme::Epsilon,
+                '<' => {
+                    let mut name = String::from("<");
+                    loop {
+                        match chars.next() {
+                            Some('>') => break,
+                            Some(c) if !c.is_whitespace() => name.push(c),
+                            _ => return Err(error(line_number, "unterminated nonterminal name")),
+                        }
+                    }
+                    name.push('>');
+                    Lexeme::NonTerminal(name)
+                }
+                '"' | '\'' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(quote) if quote == c => break,
+                            Some(c) => name.push(c),
+                            None => return Err(error(line_number, "unterminated terminal name")),
+                        }
+                    }
+                    Lexeme::Terminal(name)
+                }
+                ':' if chars.next() == Some(':') && chars.next() == Some('=') => Lexeme::Defines,
+                c if c.is_alphabetic() => {
+                    let mut word = String::from(c);
+                    while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+                        word.push(c);
+                    }
+                    if word != "epsilon" {
+                        return Err(error(
+                            line_number,
+                            &format!("unexpected `{}`; terminals must be quoted", word),
+                        ));
+                    }
+                    Lexeme::Epsilon
+                }
+                c => return Err(error(line_number, &format!("unexpected `{}`", c))),
+            };
+            lexemes.push((lexeme, line_number));
+        }
+    }
+
+    Ok(lexemes)
+}
+
+struct Loader {
+    lexemes: Vec<(Lexeme, usize)>,
+    index: usize,
+    // The code of every nonterminal that we have seen so far, by name, and the names of those that are new to us.
+    codes: HashMap<String, NonTerminal>,
+    names: BTreeMap<NonTerminal, String>,
+    next_code: u16, // The code to give to the next new nonterminal.
+    // The productions as written in the file, in order, and those that stand for `[ ... ]`, `{ ... }` and `( ... )`.
+    written: Vec<Production>,
+    generated: Vec<Production>,
+    used: BTreeMap<NonTerminal, usize>, // Every nonterminal that is used in a body, and the first line that it is used on.
+    generated_count: usize,
+}
+
+impl Loader {
+    // grammar := rule*
+    fn grammar(&mut self) -> Result<Grammar, BnfError> {
+        if self.lexemes.is_empty() {
+            return Err(error(1, "the grammar has no rules"));
+        }
+
+        let mut start = None;
+        while self.index < self.lexemes.len() {
+            let head = self.rule()?;
+            start.get_or_insert(head);
+        }
+
+        // Every nonterminal that is used has to have a rule.
+        for (code, line_number) in self.used.iter() {
+            if !self.written.iter().any(|p| p.head == *code) {
+                return Err(error(
+                    *line_number,
+                    &format!("`{}` is used but never defined", self.name(*code)),
+                ));
+            }
+        }
+
+        // The productions are numbered in the order that they were written, followed by the generated ones.
+        let mut productions = BTreeMap::new();
+        for (index, production) in self
+            .written
+            .drain(..)
+            .chain(self.generated.drain(..))
+            .enumerate()
+        {
+            let rule = u16::try_from(index + 1)
+                .map_err(|_| error(1, "the grammar has too many productions"))?;
+            productions.insert(rule, production);
+        }
+
+        Ok(Grammar {
+            start: start.unwrap(),
+            productions,
+            names: std::mem::take(&mut self.names),
+        })
+    }
+
+    // rule := nonterminal "::=" alternatives
+    fn rule(&mut self) -> Result<NonTerminal, BnfError> {
+        let (lexeme, line_number) = self.lexemes[self.index].clone();
+        let name = match lexeme {
+            Lexeme::NonTerminal(name) => name,
+            _ => return Err(error(line_number, "expected a nonterminal to begin a rule")),
+        };
+        self.index += 1;
+        if self.peek() != Some(&Lexeme::Defines) {
+            return Err(error(
+                line_number,
+                &format!("expected `::=` after `{}`", name),
+            ));
+        }
+        self.index += 1;
+
+        let head = self.code(&name, line_number)?;
+        let bodies = self.alternatives()?;
+        if let Some((lexeme, line_number)) = self.lexemes.get(self.index) {
+            if !matches!(lexeme, Lexeme::NonTerminal(_)) {
+                return Err(error(*line_number, "expected the next rule"));
+            }
+        }
+        for body in bodies {
+            self.written.push(Production { head, body });
+        }
+
+        Ok(head)
+    }
+
+    // alternatives := sequence ("|" sequence)*
+    fn alternatives(&mut self) -> Result<Vec<Vec<Symbol>>, BnfError> {
+        let mut bodies = vec![self.sequence()?];
+        while self.peek() == Some(&Lexeme::Bar) {
+            self.index += 1;
+            bodies.push(self.sequence()?);
+        }
+
+        Ok(bodies)
+    }
+
+    // sequence := item*
+    // A sequence ends at `|`, at a closing bracket, or at the beginning of the next rule.
+    fn sequence(&mut self) -> Result<Vec<Symbol>, BnfError> {
+        let mut body = Vec::new();
+
+        while let Some((lexeme, line_number)) = self.lexemes.get(self.index).cloned() {
+            match lexeme {
+                Lexeme::NonTerminal(name) => {
+                    if self.lexemes.get(self.index + 1).map(|(l, _)| l) == Some(&Lexeme::Defines) {
+                        break;
+                    }
+                    let nonterminal = self.code(&name, line_number)?;
+                    self.used.entry(nonterminal).or_insert(line_number);
+                    body.push(nonterminal.into());
+                }
+                Lexeme::Terminal(name) => match Terminal::from_name(&name) {
+                    Some(terminal) => body.push(terminal.into()),
+                    None => {
+                        return Err(error(line_number, &format!("unknown terminal `{}`", name)))
+                    }
+                },
+                Lexeme::Epsilon => {}
+                Lexeme::Open(bracket) => {
+                    self.index += 1;
+                    let nonterminal = self.bracketed(bracket, line_number)?;
+                    body.push(nonterminal.into());
+                    continue;
+                }
+                Lexeme::Defines => return Err(error(line_number, "unexpected `::=`")),
+                Lexeme::Bar | Lexeme::Close(_) => break,
+            }
+            self.index += 1;
+        }
+
+        Ok(body)
+    }
+
+    // item := "[" alternatives "]" | "{" alternatives "}" | "(" alternatives ")"
+    // Each of these is replaced by a new nonterminal, with the productions:
+    //   [ a | b ]  ->  <n> ::= a | b | ε
+    //   { a | b }  ->  <n> ::= a <n> | b <n> | ε
+    //   ( a | b )  ->  <n> ::= a | b
+    fn bracketed(&mut self, open: char, line_number: usize) -> Result<NonTerminal, BnfError> {
+        let mut bodies = self.alternatives()?;
+        let close = match open {
+            '[' => ']',
+            '{' => '}',
+            _ => ')',
+        };
+        if self.peek() != Some(&Lexeme::Close(close)) {
+            return Err(error(line_number, &format!("`{}` is never closed", open)));
+        }
+        self.index += 1;
+
+        // Find a name for the new nonterminal that is not already taken.
+        let name = loop {
+            self.generated_count += 1;
+            let name = format!("<generated-{}>", self.generated_count);
+            if !self.codes.contains_key(&name) {
+                break name;
+            }
+        };
+        let nonterminal = self.code(&name, line_number)?;
+
+        match open {
+            '[' => bodies.push(Vec::new()),
+            '{' => {
+                for body in bodies.iter_mut() {
+                    body.push(nonterminal.into());
+                }
+                bodies.push(Vec::new());
+            }
+            _ => {}
+        }
+        for body in bodies {
+            self.generated.push(Production {
+                head: nonterminal,
+                body,
+            });
+        }
+
+        Ok(nonterminal)
+    }
+
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.index).map(|(lexeme, _)| lexeme)
+    }
+
+    // The nonterminal with the given name. If we have not seen it yet, it is given a code.
+    fn code(&mut self, name: &str, line_number: usize) -> Result<NonTerminal, BnfError> {
+        if let Some(nonterminal) = self.codes.get(name) {
+            return Ok(*nonterminal);
+        }
+
+        let nonterminal = match NonTerminal::from_name(name) {
+            Some(nonterminal) => nonterminal,
+            None => loop {
+                let code = self.next_code;
+                self.next_code = code
+                    .checked_add(1)
+                    .ok_or_else(|| error(line_number, "the grammar has too many nonterminals"))?;
+                // Some codes are kept for special symbols, so those are skipped.
+                if let Some(nonterminal) = NonTerminal::new(code) {
+                    self.names.insert(nonterminal, name.to_string());
+                    break nonterminal;
+                }
+            },
+        };
+        self.codes.insert(name.to_string(), nonterminal);
+
+        Ok(nonterminal)
+    }
+
+    fn name(&self, nonterminal: NonTerminal) -> String {
+        match self.names.get(&nonterminal) {
+            Some(name) => name.clone(),
+            None => nonterminal.name().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+fn error(line_number: usize, message: &str) -> BnfError {
+    BnfError {
+        line_number,
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod bnf_tests {
+    use crate::bnf::*;
+    use crate::rules::START_SYMBOL;
+
+    // The symbols with the given legacy codes.
+    fn symbols(codes: &[u16]) -> Vec<Symbol> {
+        codes
+            .iter()
+            .map(|code| Symbol::from_code(*code).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_scala_is_the_builtin_grammar() {
+        let grammar = parse(include_str!("../grammars/simple_scala.bnf")).unwrap();
+
+        assert_eq!(grammar.start, START_SYMBOL);
+        assert_eq!(grammar.productions, Grammar::builtin().productions);
+        assert!(grammar.names.is_empty());
+    }
+
+    #[test]
+    fn test_new_nonterminals_get_new_codes() {
+        let grammar = parse("<list> ::= \"[id]\" <rest>\n<rest> ::= \",\" <list> | ε\n").unwrap();
+
+        assert_eq!(grammar.start.code(), 70);
+        let rest = NonTerminal::new(71).unwrap();
+        assert_eq!(grammar.names.get(&rest).unwrap(), "<rest>");
+        assert_eq!(grammar.productions[&1].body, symbols(&[1, 71]));
+        assert_eq!(grammar.productions[&2].body, symbols(&[37, 70]));
+        assert!(grammar.productions[&3].body.is_empty());
+    }
+
+    #[test]
+    fn test_ebnf() {
+        let grammar =
+            parse("<a> ::= [ \"package\" ] { \"[id]\" | \"[const]\" } ( \";\" | \",\" )").unwrap();
+
+        let bodies: Vec<(u16, Vec<Symbol>)> = grammar
+            .productions
+            .values()
+            .map(|p| (p.head.code(), p.body.clone()))
+            .collect();
+        assert_eq!(
+            bodies,
+            vec![
+                (70, symbols(&[71, 72, 73])),
+                (71, symbols(&[3])),
+                (71, symbols(&[])),
+                (72, symbols(&[1, 72])),
+                (72, symbols(&[2, 72])),
+                (72, symbols(&[])),
+                (73, symbols(&[31])),
+                (73, symbols(&[37])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_more_than_a_byte_of_nonterminals() {
+        let mut text = String::new();
+        for n in 0..300 {
+            text.push_str(&format!("<n{}> ::= \"[id]\" <n{}>\n", n, n + 1));
+        }
+        text.push_str("<n300> ::= ε\n");
+        let grammar = parse(&text).unwrap();
+
+        assert_eq!(grammar.nonterminals().len(), 301);
+        // The codes kept for special symbols are skipped.
+        assert!(grammar
+            .nonterminals()
+            .iter()
+            .all(|n| !(253..=255).contains(&n.code())));
+        assert!(grammar.check().conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_terminal() {
+        let result = parse("<a> ::= \"[id]\"\n      | \"var\"\n");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 2: unknown terminal `var`"
+        );
+    }
+
+    #[test]
+    fn test_undefined_nonterminal() {
+        let result = parse("<a> ::= \"[id]\" <b>\n");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 1: `<b>` is used but never defined"
+        );
+    }
+
+    #[test]
+    fn test_missing_defines() {
+        let result = parse("<a> \"[id]\"\n");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 1: expected `::=` after `<a>`"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_bracket() {
+        let result = parse("<a> ::= { \"[id]\"\n<b> ::= \";\"\n");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 1: `{` is never closed"
+        );
+    }
+}
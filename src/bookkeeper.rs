@@ -1,9 +1,15 @@
 #![warn(clippy::all)]
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::Type;
+use crate::diagnostic::Span;
+use crate::symbol::Terminal;
+use crate::trace::csv_field;
+use crate::tree::json_string;
 
 // Types of symbols in the Simple Scala programming language.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum SymbolType {
     Keyword,
     Constant,
@@ -33,7 +39,40 @@ pub struct Token {
     pub(crate) token: String,
     pub(crate) symbol_type: SymbolType,
     pub(crate) line_number: usize,
-    pub(crate) code: u8,
+    pub(crate) column: usize, // The column of the first character, counting from 1.
+    pub(crate) end_column: usize, // The column just past the last character.
+    pub(crate) span: Span,    // Where the token is in the source, in bytes.
+    pub(crate) terminal: Terminal,
+}
+
+impl Token {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn symbol_type(&self) -> &SymbolType {
+        &self.symbol_type
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn end_column(&self) -> usize {
+        self.end_column
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn terminal(&self) -> Terminal {
+        self.terminal
+    }
 }
 
 // This tells the program how to println a token in a nice way.
@@ -53,7 +92,26 @@ impl std::fmt::Display for Token {
 pub struct SymbolTableToken {
     pub(crate) token: String,
     pub(crate) symbol_type: SymbolType,
-    pub(crate) code: u8,
+    pub(crate) terminal: Terminal,
+    pub(crate) line_number: usize, // The line that it was first seen on.
+}
+
+impl SymbolTableToken {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn symbol_type(&self) -> &SymbolType {
+        &self.symbol_type
+    }
+
+    pub fn terminal(&self) -> Terminal {
+        self.terminal
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
 }
 
 // This tells the program how to println a symbol table token in a nice way.
@@ -64,7 +122,7 @@ impl std::fmt::Display for SymbolTableToken {
             "{0: <30} | {1: <30} | {2: <}",
             self.token,
             self.symbol_type.to_string(),
-            self.code
+            self.terminal.code()
         )
     }
 }
@@ -74,27 +132,527 @@ pub fn convert_token_to_symbol_table_token(tkn: Token) -> SymbolTableToken {
     SymbolTableToken {
         token: tkn.token.clone(),
         symbol_type: tkn.symbol_type,
-        code: tkn.code,
+        terminal: tkn.terminal,
+        line_number: tkn.line_number,
+    }
+}
+
+// What a declared name stands for.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SymbolKind {
+    Variable,
+    Function,
+    Parameter,
+    Package,
+}
+
+// This tells the program how to println a SymbolKind in a nice way.
+impl std::fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            SymbolKind::Variable => "variable",
+            SymbolKind::Function => "function",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Package => "package",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+// A name that was declared in a scope, filled in by the semantic analysis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    pub(crate) name: String,
+    pub(crate) kind: SymbolKind,
+    pub(crate) var_type: Option<Type>, // Only variables have a declared type.
+    pub(crate) line_number: usize,
+    pub(crate) scope: usize,
+}
+
+impl Declaration {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    pub fn var_type(&self) -> Option<Type> {
+        self.var_type
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn scope(&self) -> usize {
+        self.scope
     }
 }
 
+// A scope, such as a class body, a block or the parameters of a `def`. Every scope but the program's is inside another one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scope {
+    pub(crate) name: String,
+    pub(crate) parent: Option<usize>,
+    pub(crate) declarations: HashMap<String, usize>, // Each name declared here, and where its declaration is in the bookkeeper.
+}
+
+impl Scope {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+// Whether an occurrence of a symbol is where it was declared, or where it was used.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Role {
+    Definition,
+    Use,
+}
+
+// One place in the source where an identifier or a constant was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Occurrence {
+    pub(crate) token: String,
+    pub(crate) terminal: Terminal,
+    pub(crate) line_number: usize,
+    pub(crate) column: usize,
+    pub(crate) role: Role, // Everything is a use until the semantic analysis finds its declaration.
+    pub(crate) declaration: Option<usize>, // The declaration that it stands for, if the semantic analysis found one.
+}
+
+impl Occurrence {
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn terminal(&self) -> Terminal {
+        self.terminal
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn declaration(&self) -> Option<usize> {
+        self.declaration
+    }
+}
+
+// Every occurrence of one symbol: a declaration, or else an identifier or constant that was never declared.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossReference<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) terminal: Terminal,
+    pub(crate) declaration: Option<&'a Declaration>,
+    pub(crate) definitions: Vec<&'a Occurrence>,
+    pub(crate) uses: Vec<&'a Occurrence>,
+}
+
+impl<'a> CrossReference<'a> {
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn declaration(&self) -> Option<&Declaration> {
+        self.declaration
+    }
+
+    // Where the symbol was declared. There is more than one place if it was declared twice in the same scope.
+    pub fn definitions(&self) -> &[&'a Occurrence] {
+        &self.definitions
+    }
+
+    pub fn uses(&self) -> &[&'a Occurrence] {
+        &self.uses
+    }
+
+    // What sort of symbol this is: its kind, if it was declared, or else whether it is a constant or an identifier.
+    pub fn kind(&self) -> String {
+        match (self.declaration, self.terminal) {
+            (Some(declaration), _) => declaration.kind.to_string(),
+            (None, Terminal::Const) => "constant".to_string(),
+            (None, _) => "identifier".to_string(),
+        }
+    }
+}
+
+// The orders that the symbol table can be listed in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SymbolOrder {
+    #[default]
+    Seen, // The order that the symbols were first seen in.
+    Name,
+    Kind, // Identifiers and constants, or variables, functions, parameters and packages. By name within each kind.
+    Line, // By the line that the symbol was first seen or declared on.
+}
+
+impl SymbolOrder {
+    pub fn from_name(name: &str) -> Option<SymbolOrder> {
+        match name {
+            "seen" => Some(SymbolOrder::Seen),
+            "name" => Some(SymbolOrder::Name),
+            "kind" => Some(SymbolOrder::Kind),
+            "line" => Some(SymbolOrder::Line),
+            _ => None,
+        }
+    }
+}
+
+// The scope that every other scope is inside of. This is where packages are declared.
+pub const PROGRAM_SCOPE: usize = 0;
+
 // An implementation of our Bookkeeper.
+// It is two symbol tables in one. The flat one has every identifier and constant that the scanner found, once each. The scoped one has what the semantic analysis found declared, and where.
 #[derive(Clone, Debug)]
 pub struct Bookkeeper {
-    pub(crate) symbols: HashSet<SymbolTableToken>,
+    pub(crate) symbols: Vec<SymbolTableToken>, // In the order that they were first seen.
+    pub(crate) seen: HashSet<(String, Terminal)>, // Which symbols are in `symbols` already.
+    pub(crate) scopes: Vec<Scope>, // Indexed by the scope's number. The program's scope is first.
+    pub(crate) declarations: Vec<Declaration>, // In the order that they were declared.
+    pub(crate) occurrences: Vec<Occurrence>, // In the order that they are in the source.
+    pub(crate) positions: HashMap<(usize, usize), usize>, // Which occurrence is at each line and column.
 }
 
 impl Bookkeeper {
     // Create a new bookkeeper.
     pub fn new() -> Self {
         Bookkeeper {
-            symbols: HashSet::new(),
+            symbols: Vec::new(),
+            seen: HashSet::new(),
+            scopes: vec![Scope {
+                name: "program".to_string(),
+                parent: None,
+                declarations: HashMap::new(),
+            }],
+            declarations: Vec::new(),
+            occurrences: Vec::new(),
+            positions: HashMap::new(),
         }
     }
 
-    // Insert a token into the bookkeeper, prevent the creation of duplicates using a HashSet.
+    // Insert a token into the bookkeeper, unless it is there already. The first time that a token is seen is the one that is kept.
     pub fn insert(&mut self, t: SymbolTableToken) {
-        self.symbols.insert(t);
+        if self.seen.insert((t.token.clone(), t.terminal)) {
+            self.symbols.push(t);
+        }
+    }
+
+    // Record a place where an identifier or constant was found. It counts as a use until it is marked otherwise.
+    pub fn record(&mut self, tkn: &Token) {
+        self.positions
+            .insert((tkn.line_number, tkn.column), self.occurrences.len());
+        self.occurrences.push(Occurrence {
+            token: tkn.token.clone(),
+            terminal: tkn.terminal,
+            line_number: tkn.line_number,
+            column: tkn.column,
+            role: Role::Use,
+            declaration: None,
+        });
+    }
+
+    // Mark the occurrence at a line and column as the definition or a use of a declaration. Nothing happens if nothing was recorded there.
+    pub fn mark(&mut self, line_number: usize, column: usize, role: Role, declaration: usize) {
+        if let Some(index) = self.positions.get(&(line_number, column)) {
+            let occurrence = &mut self.occurrences[*index];
+            occurrence.role = role;
+            occurrence.declaration = Some(declaration);
+        }
+    }
+
+    // Every place that an identifier or constant was found, in the order that they are in the source.
+    pub fn occurrences(&self) -> &[Occurrence] {
+        &self.occurrences
+    }
+
+    // Every occurrence of each symbol, with the symbols in the order that they first occur.
+    // A symbol is a declaration if the semantic analysis found one. Otherwise it is an identifier or a constant, and all of its occurrences are uses.
+    pub fn cross_references(&self) -> Vec<CrossReference<'_>> {
+        let mut references: Vec<CrossReference> = Vec::new();
+        let mut found: HashMap<(Option<usize>, &str, Terminal), usize> = HashMap::new();
+        for occurrence in &self.occurrences {
+            // Every occurrence of a declaration has its name, so the name only tells apart the symbols that were never declared.
+            let key = match occurrence.declaration {
+                Some(declaration) => (Some(declaration), "", occurrence.terminal),
+                None => (None, occurrence.token.as_str(), occurrence.terminal),
+            };
+            let index = *found.entry(key).or_insert_with(|| {
+                references.push(CrossReference {
+                    name: &occurrence.token,
+                    terminal: occurrence.terminal,
+                    declaration: occurrence
+                        .declaration
+                        .map(|index| &self.declarations[index]),
+                    definitions: Vec::new(),
+                    uses: Vec::new(),
+                });
+                references.len() - 1
+            });
+
+            match occurrence.role {
+                Role::Definition => references[index].definitions.push(occurrence),
+                Role::Use => references[index].uses.push(occurrence),
+            }
+        }
+
+        references
+    }
+
+    // The cross-reference listing as a table, with a line for each symbol. The places are written as line:column.
+    pub fn cross_references_to_string(&self) -> String {
+        let sites = |occurrences: &[&Occurrence]| -> String {
+            let sites: Vec<String> = occurrences
+                .iter()
+                .map(|occurrence| format!("{}:{}", occurrence.line_number, occurrence.column))
+                .collect();
+            match sites.len() {
+                0 => "-".to_string(),
+                _ => sites.join(", "),
+            }
+        };
+
+        let mut s = format!(
+            "{0: <30} | {1: <10} | {2: <30} | {3: <10} | {4: <}\n",
+            "Symbol", "Kind", "Scope", "Defined", "Used"
+        );
+        for reference in self.cross_references() {
+            let scope = reference
+                .declaration
+                .map_or("-".to_string(), |declaration| {
+                    self.scope_path(declaration.scope)
+                });
+            s.push_str(&format!(
+                "{0: <30} | {1: <10} | {2: <30} | {3: <10} | {4: <}\n",
+                reference.name,
+                reference.kind(),
+                scope,
+                sites(&reference.definitions),
+                sites(&reference.uses)
+            ));
+        }
+
+        s
+    }
+
+    // Every entry in the symbol table, in the order that they were first seen.
+    pub fn symbols(&self) -> impl Iterator<Item = &SymbolTableToken> {
+        self.symbols.iter()
+    }
+
+    // Every entry in the symbol table, in the given order.
+    pub fn sorted_symbols(&self, order: SymbolOrder) -> Vec<&SymbolTableToken> {
+        let mut symbols: Vec<&SymbolTableToken> = self.symbols.iter().collect();
+        // The sorts are stable, so symbols that are tied stay in the order that they were seen.
+        match order {
+            SymbolOrder::Seen => {}
+            SymbolOrder::Name => symbols.sort_by(|a, b| a.token.cmp(&b.token)),
+            SymbolOrder::Kind => {
+                symbols.sort_by(|a, b| (&a.symbol_type, &a.token).cmp(&(&b.symbol_type, &b.token)))
+            }
+            SymbolOrder::Line => symbols.sort_by_key(|symbol| symbol.line_number),
+        }
+
+        symbols
+    }
+
+    // The symbol table as a table, with a line for each entry.
+    pub fn symbols_to_string(&self, order: SymbolOrder) -> String {
+        let mut s = format!(
+            "{0: <30} | {1: <30} | {2: <}\n",
+            "Token", "Symbol Type", "Code"
+        );
+        for symbol in self.sorted_symbols(order) {
+            s.push_str(&format!("{}\n", symbol));
+        }
+
+        s
+    }
+
+    // The symbol table as CSV, with a header row.
+    pub fn symbols_to_csv(&self, order: SymbolOrder) -> String {
+        let mut s = String::from("token,symbol_type,code,line\n");
+        for symbol in self.sorted_symbols(order) {
+            s.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_field(&symbol.token),
+                symbol.symbol_type,
+                symbol.terminal.code(),
+                symbol.line_number
+            ));
+        }
+
+        s
+    }
+
+    // The symbol table as a JSON array, with an object for each entry.
+    pub fn symbols_to_json(&self, order: SymbolOrder) -> String {
+        let symbols: Vec<String> = self
+            .sorted_symbols(order)
+            .iter()
+            .map(|symbol| {
+                format!(
+                    "{{\"token\":{},\"symbol_type\":{},\"code\":{},\"line\":{}}}",
+                    json_string(&symbol.token),
+                    json_string(&symbol.symbol_type.to_string()),
+                    symbol.terminal.code(),
+                    symbol.line_number
+                )
+            })
+            .collect();
+
+        format!("[{}]", symbols.join(","))
+    }
+
+    // Open a new scope inside another one, and return its number.
+    pub fn open_scope(&mut self, parent: usize, name: &str) -> usize {
+        self.scopes.push(Scope {
+            name: name.to_string(),
+            parent: Some(parent),
+            declarations: HashMap::new(),
+        });
+
+        self.scopes.len() - 1
+    }
+
+    pub fn scope(&self, scope: usize) -> &Scope {
+        &self.scopes[scope]
+    }
+
+    // The names of a scope and of every scope that it is inside of, from the outside in, e.g. `program::class 1::def x`.
+    pub fn scope_path(&self, scope: usize) -> String {
+        let mut names = vec![self.scopes[scope].name.as_str()];
+        let mut scope = &self.scopes[scope];
+        while let Some(parent) = scope.parent {
+            scope = &self.scopes[parent];
+            names.push(&scope.name);
+        }
+        names.reverse();
+
+        names.join("::")
+    }
+
+    // Declare a name in a scope, and return the number of the declaration. If it was already declared there, the first declaration stays, and its number is returned as an error.
+    pub fn declare(
+        &mut self,
+        scope: usize,
+        name: &str,
+        kind: SymbolKind,
+        var_type: Option<Type>,
+        line_number: usize,
+    ) -> Result<usize, usize> {
+        if let Some(index) = self.scopes[scope].declarations.get(name) {
+            return Err(*index);
+        }
+
+        let index = self.declarations.len();
+        self.scopes[scope]
+            .declarations
+            .insert(name.to_string(), index);
+        self.declarations.push(Declaration {
+            name: name.to_string(),
+            kind,
+            var_type,
+            line_number,
+            scope,
+        });
+
+        Ok(index)
+    }
+
+    pub fn declaration(&self, index: usize) -> &Declaration {
+        &self.declarations[index]
+    }
+
+    // The declaration of a name in a scope, leaving out the scopes around it.
+    pub fn lookup_local(&self, scope: usize, name: &str) -> Option<&Declaration> {
+        self.scopes[scope]
+            .declarations
+            .get(name)
+            .map(|index| &self.declarations[*index])
+    }
+
+    // The declaration that a name in a scope stands for: the one in the scope itself, or else the one in the nearest scope around it.
+    pub fn lookup(&self, scope: usize, name: &str) -> Option<&Declaration> {
+        self.lookup_index(scope, name)
+            .map(|index| &self.declarations[index])
+    }
+
+    // The same as `lookup`, but for the number of the declaration.
+    pub fn lookup_index(&self, scope: usize, name: &str) -> Option<usize> {
+        let mut scope = Some(scope);
+        while let Some(current) = scope {
+            if let Some(index) = self.scopes[current].declarations.get(name) {
+                return Some(*index);
+            }
+            scope = self.scopes[current].parent;
+        }
+
+        None
+    }
+
+    // Every declaration, in the order that they were declared.
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    // Every declaration, in the given order.
+    pub fn sorted_declarations(&self, order: SymbolOrder) -> Vec<&Declaration> {
+        let mut declarations: Vec<&Declaration> = self.declarations.iter().collect();
+        match order {
+            SymbolOrder::Seen => {}
+            SymbolOrder::Name => declarations.sort_by(|a, b| a.name.cmp(&b.name)),
+            SymbolOrder::Kind => {
+                declarations.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)))
+            }
+            SymbolOrder::Line => declarations.sort_by_key(|declaration| declaration.line_number),
+        }
+
+        declarations
+    }
+
+    // The scoped symbol table as a table, with a line for each declaration.
+    pub fn scopes_to_string(&self, order: SymbolOrder) -> String {
+        let mut s = format!(
+            "{0: <30} | {1: <10} | {2: <5} | {3: <5} | {4: <}\n",
+            "Name", "Kind", "Type", "Line", "Scope"
+        );
+        for declaration in self.sorted_declarations(order) {
+            let var_type = declaration
+                .var_type
+                .map_or("-".to_string(), |var_type| var_type.to_string());
+            s.push_str(&format!(
+                "{0: <30} | {1: <10} | {2: <5} | {3: <5} | {4: <}\n",
+                declaration.name,
+                declaration.kind.to_string(),
+                var_type,
+                declaration.line_number,
+                self.scope_path(declaration.scope)
+            ));
+        }
+
+        s
+    }
+}
+
+impl Default for Bookkeeper {
+    fn default() -> Self {
+        Bookkeeper::new()
     }
 }
 
@@ -110,7 +668,8 @@ mod symbol_table_tests {
         let tkn = SymbolTableToken {
             token: "test".to_string(),
             symbol_type: SymbolType::Identifier,
-            code: 1,
+            terminal: Terminal::Id,
+            line_number: 1,
         };
 
         symtab.insert(tkn);
@@ -127,7 +686,8 @@ mod symbol_table_tests {
         let tkn = SymbolTableToken {
             token: "test".to_string(),
             symbol_type: SymbolType::Identifier,
-            code: 1,
+            terminal: Terminal::Id,
+            line_number: 1,
         };
 
         let dup_tkn = tkn.clone();
@@ -140,4 +700,164 @@ mod symbol_table_tests {
 
         assert_eq!(expected, actual);
     }
+
+    // A symbol table with the identifiers and constants of a program, in the order that they were first seen.
+    fn symtab_of(names: &[(&str, usize)]) -> Bookkeeper {
+        let mut symtab = Bookkeeper::new();
+        for (name, line_number) in names {
+            let constant = name.starts_with(|c: char| c.is_ascii_digit());
+            symtab.insert(SymbolTableToken {
+                token: name.to_string(),
+                symbol_type: if constant {
+                    SymbolType::Constant
+                } else {
+                    SymbolType::Identifier
+                },
+                terminal: if constant {
+                    Terminal::Const
+                } else {
+                    Terminal::Id
+                },
+                line_number: *line_number,
+            });
+        }
+
+        symtab
+    }
+
+    #[test]
+    fn test_first_occurrence_order() {
+        let symtab = symtab_of(&[("b", 1), ("25", 1), ("a", 2), ("b", 3), ("1.5", 3)]);
+
+        let names = |order| -> Vec<&str> {
+            symtab
+                .sorted_symbols(order)
+                .iter()
+                .map(|symbol| symbol.token())
+                .collect()
+        };
+        assert_eq!(names(SymbolOrder::Seen), vec!["b", "25", "a", "1.5"]);
+        assert_eq!(names(SymbolOrder::Name), vec!["1.5", "25", "a", "b"]);
+        assert_eq!(names(SymbolOrder::Kind), vec!["1.5", "25", "a", "b"]);
+        assert_eq!(names(SymbolOrder::Line), vec!["b", "25", "a", "1.5"]);
+        assert_eq!(symtab.sorted_symbols(SymbolOrder::Seen)[0].line_number(), 1);
+        assert_eq!(SymbolOrder::from_name("kind"), Some(SymbolOrder::Kind));
+        assert_eq!(SymbolOrder::from_name("size"), None);
+    }
+
+    #[test]
+    fn test_export() {
+        let symtab = symtab_of(&[("x", 1), ("4,5", 2)]);
+
+        assert_eq!(
+            symtab.symbols_to_csv(SymbolOrder::Seen),
+            format!(
+                "token,symbol_type,code,line\nx,Identifier,{},1\n\"4,5\",Constant,{},2\n",
+                Terminal::Id.code(),
+                Terminal::Const.code()
+            )
+        );
+        assert_eq!(
+            symtab.symbols_to_json(SymbolOrder::Name),
+            format!(
+                "[{{\"token\":\"4,5\",\"symbol_type\":\"Constant\",\"code\":{},\"line\":2}},{{\"token\":\"x\",\"symbol_type\":\"Identifier\",\"code\":{},\"line\":1}}]",
+                Terminal::Const.code(),
+                Terminal::Id.code()
+            )
+        );
+        assert!(symtab
+            .symbols_to_string(SymbolOrder::Seen)
+            .starts_with("Token "));
+    }
+
+    #[test]
+    fn test_cross_references() {
+        let mut symtab = Bookkeeper::new();
+        let tkn = |token: &str, terminal: Terminal, line_number: usize, column: usize| Token {
+            token: token.to_string(),
+            symbol_type: SymbolType::Identifier,
+            line_number,
+            column,
+            end_column: column + token.len(),
+            span: Span::default(),
+            terminal,
+        };
+        symtab.record(&tkn("a", Terminal::Id, 1, 5));
+        symtab.record(&tkn("a", Terminal::Id, 2, 1));
+        symtab.record(&tkn("1", Terminal::Const, 2, 6));
+        symtab.record(&tkn("b", Terminal::Id, 3, 1));
+        symtab.record(&tkn("1", Terminal::Const, 3, 6));
+        symtab.record(&tkn("a", Terminal::Id, 3, 10));
+
+        // Only the first two `a`s are the declared one; the last is some other `a`, which was never declared.
+        let a = symtab
+            .declare(PROGRAM_SCOPE, "a", SymbolKind::Variable, Some(Type::Int), 1)
+            .unwrap();
+        symtab.mark(1, 5, Role::Definition, a);
+        symtab.mark(2, 1, Role::Use, a);
+        symtab.mark(9, 9, Role::Use, a);
+
+        let references = symtab.cross_references();
+        assert_eq!(references.len(), 4);
+        assert_eq!(references[0].kind(), "variable");
+        assert_eq!(references[0].definitions()[0].column(), 5);
+        assert_eq!(references[0].uses()[0].line_number(), 2);
+        assert_eq!(references[1].kind(), "constant");
+        assert_eq!(references[1].uses().len(), 2);
+        assert_eq!(references[3].name(), "a");
+        assert_eq!(references[3].kind(), "identifier");
+        assert!(references[3].declaration().is_none());
+
+        let report = symtab.cross_references_to_string();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("a "));
+        assert!(lines[1].ends_with("| 1:5        | 2:1"));
+        assert!(lines[2].ends_with("| -          | 2:6, 3:6"));
+    }
+
+    #[test]
+    fn test_scopes() {
+        let mut symtab = Bookkeeper::new();
+        let class = symtab.open_scope(PROGRAM_SCOPE, "class 1");
+        let def = symtab.open_scope(class, "def f");
+
+        symtab
+            .declare(PROGRAM_SCOPE, "a", SymbolKind::Package, None, 1)
+            .unwrap();
+        symtab
+            .declare(class, "a", SymbolKind::Variable, Some(Type::Int), 3)
+            .unwrap();
+        symtab
+            .declare(def, "x", SymbolKind::Parameter, None, 4)
+            .unwrap();
+        let duplicate = symtab
+            .declare(class, "a", SymbolKind::Function, None, 5)
+            .unwrap_err();
+        assert_eq!(symtab.declaration(duplicate).line_number(), 3);
+
+        assert_eq!(symtab.lookup(def, "a").unwrap().var_type(), Some(Type::Int));
+        assert_eq!(
+            symtab.lookup(PROGRAM_SCOPE, "a").unwrap().kind(),
+            SymbolKind::Package
+        );
+        assert!(symtab.lookup(class, "x").is_none());
+        assert!(symtab.lookup_local(def, "a").is_none());
+        assert_eq!(symtab.scope_path(def), "program::class 1::def f");
+        assert_eq!(symtab.declarations().len(), 3);
+
+        let kinds: Vec<SymbolKind> = symtab
+            .sorted_declarations(SymbolOrder::Kind)
+            .iter()
+            .map(|declaration| declaration.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SymbolKind::Variable,
+                SymbolKind::Parameter,
+                SymbolKind::Package
+            ]
+        );
+    }
 }
@@ -0,0 +1,153 @@
+#![warn(clippy::all)]
+
+// A cursor over the source, which the scanner moves along one character at a time.
+// It keeps a byte offset rather than a character index, so that looking at the next (or previous) character takes constant time however far into the source we are.
+// Multi-byte UTF-8 characters are stepped over whole.
+
+// Where a character is in the source: its byte offset, and its column, counted in characters from 1.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Position {
+    pub(crate) offset: usize,
+    pub(crate) column: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Cursor {
+    source: String,
+    position: Position, // The position of the next character.
+    previous: Position, // The position of the character that was last stepped over.
+}
+
+impl Cursor {
+    pub fn new(source: String) -> Self {
+        Cursor {
+            source,
+            position: Position {
+                offset: 0,
+                column: 1,
+            },
+            previous: Position::default(),
+        }
+    }
+
+    // The next character, without moving past it.
+    pub fn peek(&self) -> Option<char> {
+        self.source[self.position.offset..].chars().next()
+    }
+
+    // The character that was last stepped over.
+    pub fn previous(&self) -> Option<char> {
+        self.source[..self.position.offset].chars().next_back()
+    }
+
+    // Step over the next character, and return it.
+    pub fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+
+        self.previous = self.position;
+        self.position.offset += c.len_utf8();
+        if c == '\n' {
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(c)
+    }
+
+    // Jump straight to the end of the source.
+    pub fn skip_to_end(&mut self) {
+        while self.advance().is_some() {}
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn previous_position(&self) -> Position {
+        self.previous
+    }
+
+    // Determine whether we have stepped over every character in the source.
+    pub fn is_done(&self) -> bool {
+        self.position.offset >= self.source.len()
+    }
+}
+
+#[cfg(test)]
+mod cursor_tests {
+    use crate::cursor::*;
+
+    #[test]
+    fn test_peek_and_advance() {
+        let mut cursor = Cursor::new("ab".to_string());
+
+        assert_eq!(cursor.peek(), Some('a'));
+        assert_eq!(cursor.previous(), None);
+        assert_eq!(cursor.advance(), Some('a'));
+        assert_eq!(cursor.peek(), Some('b'));
+        assert_eq!(cursor.previous(), Some('a'));
+        assert_eq!(cursor.advance(), Some('b'));
+        assert!(cursor.is_done());
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.advance(), None);
+    }
+
+    #[test]
+    fn test_multi_byte_characters() {
+        let mut cursor = Cursor::new("é€a".to_string());
+
+        assert_eq!(cursor.advance(), Some('é'));
+        assert_eq!(cursor.advance(), Some('€'));
+        assert_eq!(
+            cursor.previous_position(),
+            Position {
+                offset: 2,
+                column: 2
+            }
+        );
+        assert_eq!(
+            cursor.position(),
+            Position {
+                offset: 5,
+                column: 3
+            }
+        );
+        assert_eq!(cursor.previous(), Some('€'));
+        assert_eq!(cursor.advance(), Some('a'));
+        assert!(cursor.is_done());
+    }
+
+    #[test]
+    fn test_columns_restart_on_each_line() {
+        let mut cursor = Cursor::new("ab\ncd".to_string());
+
+        cursor.advance();
+        cursor.advance();
+        cursor.advance();
+        assert_eq!(
+            cursor.previous_position(),
+            Position {
+                offset: 2,
+                column: 3
+            }
+        );
+        assert_eq!(
+            cursor.position(),
+            Position {
+                offset: 3,
+                column: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_skip_to_end() {
+        let mut cursor = Cursor::new("package a;".to_string());
+
+        cursor.skip_to_end();
+
+        assert!(cursor.is_done());
+        assert_eq!(cursor.previous(), Some(';'));
+    }
+}
@@ -0,0 +1,134 @@
+#![warn(clippy::all)]
+
+// The leftmost derivation of an accepted program, as a sequence of sentential forms.
+// The PDA only keeps the rules that it applied, in order. Since an LL(1) parse always expands the leftmost nonterminal, replaying the rules from the start symbol gives back each sentential form.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::symbol::{NonTerminal, Symbol};
+
+#[derive(Clone, Debug)]
+pub struct Derivation {
+    pub(crate) start: NonTerminal,
+    pub(crate) rules: Vec<u16>, // The rules that were applied, in order.
+    pub(crate) expansion_rules: HashMap<u16, Vec<Symbol>>,
+    pub(crate) names: BTreeMap<NonTerminal, String>, // The names of any nonterminals that are not in the built-in grammar.
+}
+
+impl Derivation {
+    pub fn rules(&self) -> &[u16] {
+        &self.rules
+    }
+
+    // Every sentential form, starting with the start symbol and ending with the program's terminals.
+    // There is one more form than there are rules.
+    pub fn sentential_forms(&self) -> Vec<Vec<Symbol>> {
+        let mut forms: Vec<Vec<Symbol>> = Vec::new();
+        self.replay(|_, form| forms.push(form.to_vec()));
+
+        forms
+    }
+
+    // Apply each rule in turn to the leftmost nonterminal, and hand each sentential form to `visit` along with the rule that produced it.
+    fn replay(&self, mut visit: impl FnMut(Option<u16>, &[Symbol])) {
+        let mut form: Vec<Symbol> = vec![self.start.into()];
+        // Everything before this is terminals, so the search for the leftmost nonterminal can start here.
+        let mut leftmost = 0;
+        visit(None, &form);
+
+        for rule in &self.rules {
+            while !matches!(form[leftmost], Symbol::NonTerminal(_)) {
+                leftmost += 1;
+            }
+            let body = &self.expansion_rules[rule];
+            form.splice(leftmost..=leftmost, body.iter().copied());
+            visit(Some(*rule), &form);
+        }
+    }
+
+    fn symbol_name(&self, symbol: Symbol) -> String {
+        symbol.name_in(&self.names)
+    }
+}
+
+// This tells the program how to println a derivation in a nice way, one sentential form per line with the rule that was applied to get it.
+impl std::fmt::Display for Derivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut result = Ok(());
+        self.replay(|rule, form| {
+            let names: Vec<String> = form.iter().map(|s| self.symbol_name(*s)).collect();
+            // An empty sentential form is what is left once an epsilon rule is applied to the only symbol.
+            let form = if names.is_empty() {
+                "epsilon".to_string()
+            } else {
+                names.join(" ")
+            };
+            result = result.and_then(|_| match rule {
+                None => writeln!(f, "   {}", form),
+                Some(rule) => writeln!(f, "=> {0: <80} (rule {1})", form, rule),
+            });
+        });
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod derivation_tests {
+    use crate::bookkeeper::Bookkeeper;
+    use crate::derivation::*;
+    use crate::parser::Parser;
+    use crate::symbol::Terminal;
+    use crate::trace::SilentTrace;
+
+    fn derivation(source: &str) -> Option<Derivation> {
+        let mut parser = Parser::new(source.to_string(), Bookkeeper::new());
+        parser.parse_with(&mut SilentTrace);
+
+        parser.derivation()
+    }
+
+    #[test]
+    fn test_sentential_forms() {
+        let derivation = derivation("package a;\n$\n").unwrap();
+        let forms = derivation.sentential_forms();
+
+        assert_eq!(derivation.rules(), &[1, 2, 3, 5, 7]);
+        assert_eq!(forms.len(), 6);
+        assert_eq!(forms[0], vec![NonTerminal::SCALA.into()]);
+        assert_eq!(
+            forms[1],
+            vec![
+                NonTerminal::PACKAGES.into(),
+                NonTerminal::IMPORTS.into(),
+                NonTerminal::SCALA_BODY.into()
+            ]
+        );
+        // The last form is the program itself.
+        assert_eq!(
+            forms[5],
+            vec![
+                Terminal::Package.into(),
+                Terminal::Id.into(),
+                Terminal::Semicolon.into()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let text = derivation("package a;\n$\n").unwrap().to_string();
+        let lines: Vec<&str> = text.lines().map(|line| line.trim_end()).collect();
+
+        assert_eq!(lines[0], "   <scala>");
+        assert!(lines[1].starts_with("=> <packages> <imports> <scala-body> "));
+        assert!(lines[1].ends_with("(rule 1)"));
+        assert!(lines[5].starts_with("=> package [id] ; "));
+        assert!(lines[5].ends_with("(rule 7)"));
+    }
+
+    #[test]
+    fn test_rejected_program_has_no_derivation() {
+        assert!(derivation("package a\n$\n").is_none());
+    }
+}
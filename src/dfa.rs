@@ -0,0 +1,381 @@
+#![warn(clippy::all)]
+
+// The DFA that the scanner runs to recognize tokens.
+// Every token is defined by a regex in token_specs(), and the lexer generator in lexgen.rs turns them into a minimal DFA. Adding a keyword is just a matter of adding it to KEYWORDS.
+// The scanner only runs the DFA over the characters of a token: it sets aside whitespace, comments and special symbols that are stuck to the end of a token before the DFA sees them.
+// So the table here says what to do with each character in each state: move on, finish the token (when the character is the separator after it), or report an error.
+
+use crate::bookkeeper::SymbolType;
+use crate::error::ErrorType;
+use crate::lexgen::{column, representative, Automaton, RegexError, COLUMNS};
+use crate::scanner::is_separator;
+use crate::symbol::Terminal;
+
+// The keywords of Simple Scala.
+pub const KEYWORDS: [(&str, Terminal); 26] = [
+    ("package", Terminal::Package),
+    ("import", Terminal::Import),
+    ("abstract", Terminal::Abstract),
+    ("final", Terminal::Final),
+    ("sealed", Terminal::Sealed),
+    ("private", Terminal::Private),
+    ("protected", Terminal::Protected),
+    ("class", Terminal::Class),
+    ("object", Terminal::Object),
+    ("val", Terminal::Val),
+    ("def", Terminal::Def),
+    ("if", Terminal::If),
+    ("else", Terminal::Else),
+    ("while", Terminal::While),
+    ("case", Terminal::Case),
+    ("in", Terminal::In),
+    ("print", Terminal::Print),
+    ("return", Terminal::Return),
+    ("not", Terminal::Not),
+    ("true", Terminal::True),
+    ("false", Terminal::False),
+    ("and", Terminal::And),
+    ("or", Terminal::Or),
+    ("int", Terminal::Int),
+    ("real", Terminal::Real),
+    ("bool", Terminal::Bool),
+];
+
+// The symbols that can be longer than one character, along with `=`, which begins one of them.
+pub const OPERATORS: [(&str, SymbolType, Terminal); 3] = [
+    ("=", SymbolType::SpecialSymbol, Terminal::Equals),
+    ("=>", SymbolType::Keyword, Terminal::Arrow),
+    ("<=", SymbolType::Keyword, Terminal::Assign),
+];
+
+pub const START: usize = 0;
+
+// What a token definition stands for, once it has been matched.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Token(SymbolType, Terminal),
+    Error(ErrorType), // Some definitions are of tokens that are known to be wrong, so that they can be reported as such.
+}
+
+// A token definition.
+#[derive(Clone, Debug)]
+pub struct TokenSpec {
+    pub(crate) pattern: String,
+    pub(crate) outcome: Outcome,
+    // Whether the token has to be followed by a separator. Special symbols are separators themselves, so they end the token straight away.
+    pub(crate) delimited: bool,
+}
+
+impl TokenSpec {
+    fn new(pattern: &str, outcome: Outcome, delimited: bool) -> Self {
+        TokenSpec {
+            pattern: pattern.to_string(),
+            outcome,
+            delimited,
+        }
+    }
+}
+
+// The token definitions of Simple Scala. Where two of them match the same text, the earlier one wins, so the keywords come before identifiers.
+pub fn token_specs() -> Vec<TokenSpec> {
+    let token = |symbol_type: SymbolType, terminal: Terminal| Outcome::Token(symbol_type, terminal);
+    let mut specs: Vec<TokenSpec> = Vec::new();
+
+    for (keyword, terminal) in KEYWORDS {
+        specs.push(TokenSpec::new(
+            keyword,
+            token(SymbolType::Keyword, terminal),
+            true,
+        ));
+    }
+    for (operator, symbol_type, terminal) in OPERATORS {
+        specs.push(TokenSpec::new(operator, token(symbol_type, terminal), true));
+    }
+    specs.push(TokenSpec::new(
+        "[a-zA-Z][a-zA-Z0-9.]*",
+        token(SymbolType::Identifier, Terminal::Id),
+        true,
+    ));
+    specs.push(TokenSpec::new(
+        "[0-9]+(\\.[0-9]*)?|\\.[0-9]+",
+        token(SymbolType::Constant, Terminal::Const),
+        true,
+    ));
+    // A comment is just the `#`. The scanner skips the rest of the line.
+    for (symbol, terminal) in [
+        ("#", Terminal::Comment),
+        (";", Terminal::Semicolon),
+        ("{", Terminal::LeftBrace),
+        ("}", Terminal::RightBrace),
+        ("\\(", Terminal::LeftParen),
+        ("\\)", Terminal::RightParen),
+        (":", Terminal::Colon),
+        (",", Terminal::Comma),
+        ("\\+", Terminal::Plus),
+        ("\\*", Terminal::Star),
+        ("@", Terminal::At),
+    ] {
+        specs.push(TokenSpec::new(
+            symbol,
+            token(SymbolType::SpecialSymbol, terminal),
+            false,
+        ));
+    }
+
+    // Known errors. Once one of these is certain, the rest of the token is skipped, up to the next separator.
+    // A second period right after a leading period is reported straight away.
+    specs.push(TokenSpec::new(
+        "[0-9]+\\.[0-9]*\\.\\S*",
+        Outcome::Error(ErrorType::ConstantHasTooManyPeriods),
+        true,
+    ));
+    specs.push(TokenSpec::new(
+        "\\.[0-9]*\\.",
+        Outcome::Error(ErrorType::ConstantHasTooManyPeriods),
+        false,
+    ));
+    specs.push(TokenSpec::new(
+        "[0-9]+\\a\\S*",
+        Outcome::Error(ErrorType::IdentifierBeginsWithNumber),
+        true,
+    ));
+    // Part way through a keyword or an operator, a character that is not allowed is reported straight away.
+    let mut prefixes: Vec<&str> = Vec::new();
+    for text in KEYWORDS
+        .iter()
+        .map(|(keyword, _)| *keyword)
+        .chain(OPERATORS.iter().map(|(operator, _, _)| *operator))
+    {
+        for end in 1..=text.len() {
+            if !prefixes.contains(&&text[..end]) {
+                prefixes.push(&text[..end]);
+            }
+        }
+    }
+    specs.push(TokenSpec::new(
+        &format!("({})[^a-zA-Z0-9.\\s]", prefixes.join("|")),
+        Outcome::Error(ErrorType::InvalidSymbol),
+        false,
+    ));
+    specs.push(TokenSpec::new(
+        "[a-zA-Z][a-zA-Z0-9.]*[^a-zA-Z0-9.\\s]\\S*",
+        Outcome::Error(ErrorType::InvalidSymbol),
+        true,
+    ));
+
+    specs
+}
+
+// What the DFA does when it reads a character in a given state.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transition {
+    Goto(usize),                 // Move to the state, and read another character.
+    Token(SymbolType, Terminal), // The token is finished.
+    Error(ErrorType),            // The token is in error.
+}
+
+impl From<Outcome> for Transition {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Token(symbol_type, terminal) => Transition::Token(symbol_type, terminal),
+            Outcome::Error(error_type) => Transition::Error(error_type),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Dfa {
+    pub(crate) transitions: Vec<Vec<Transition>>, // One row of COLUMNS transitions for each state.
+    pub(crate) accepts: Vec<Option<Outcome>>, // What each state stands for, if the token were to end there.
+    pub(crate) names: Vec<String>, // A name for each state: the shortest text that leads to it.
+}
+
+lazy_static! {
+    // The DFA of Simple Scala.
+    pub static ref DFA: Dfa = Dfa::new(&token_specs()).unwrap();
+}
+
+impl Dfa {
+    // Generate the DFA for the given token definitions.
+    // Any character that the definitions do not allow for is an invalid symbol, and so is a separator after text that is not a token.
+    pub fn new(specs: &[TokenSpec]) -> Result<Self, RegexError> {
+        let patterns: Vec<&str> = specs.iter().map(|spec| spec.pattern.as_str()).collect();
+        let automaton = Automaton::generate(&patterns)?;
+
+        let transitions = (0..automaton.transitions.len())
+            .map(|state| {
+                (0..COLUMNS)
+                    .map(|column| match automaton.transitions[state][column] {
+                        // A token that needs no separator is finished as soon as it is matched.
+                        Some(next) => match automaton.accepts[next].map(|spec| &specs[spec]) {
+                            Some(spec) if !spec.delimited => spec.outcome.clone().into(),
+                            _ => Transition::Goto(next),
+                        },
+                        None if is_separator(representative(column)) => {
+                            match automaton.accepts[state] {
+                                Some(spec) => specs[spec].outcome.clone().into(),
+                                None => Transition::Error(ErrorType::InvalidSymbol),
+                            }
+                        }
+                        None => Transition::Error(ErrorType::InvalidSymbol),
+                    })
+                    .collect()
+            })
+            .collect();
+        let accepts = automaton
+            .accepts
+            .iter()
+            .map(|spec| spec.map(|spec| specs[spec].outcome.clone()))
+            .collect();
+        let mut names = automaton.shortest_paths();
+        names[START] = "start".to_string();
+
+        Ok(Dfa {
+            transitions,
+            accepts,
+            names,
+        })
+    }
+
+    // What to do on reading the character in the given state.
+    pub fn next(&self, state: usize, c: char) -> &Transition {
+        &self.transitions[state][column(c)]
+    }
+
+    // What the token would be if it ended in the given state.
+    pub fn outcome(&self, state: usize) -> Option<&Outcome> {
+        self.accepts[state].as_ref()
+    }
+}
+
+#[cfg(test)]
+mod dfa_tests {
+    use crate::dfa::*;
+
+    // Run the DFA over a token and the separator after it, as the scanner would.
+    fn run(text: &str) -> Transition {
+        let mut state = START;
+        for c in text.chars() {
+            match DFA.next(state, c) {
+                Transition::Goto(next) => state = *next,
+                transition => return transition.clone(),
+            }
+        }
+
+        DFA.next(state, ' ').clone()
+    }
+
+    // The state that the DFA is in after reading the text, which must not end the token.
+    fn walk(text: &str) -> usize {
+        text.chars()
+            .fold(START, |state, c| match DFA.next(state, c) {
+                Transition::Goto(next) => *next,
+                transition => panic!("{}: {:?}", text, transition),
+            })
+    }
+
+    #[test]
+    fn test_every_keyword() {
+        for (keyword, terminal) in KEYWORDS {
+            assert_eq!(
+                run(keyword),
+                Transition::Token(SymbolType::Keyword, terminal)
+            );
+        }
+    }
+
+    #[test]
+    fn test_prefixes_and_extensions_of_keywords_are_identifiers() {
+        for text in ["pack", "i", "integer", "classes", "a.b", "x1"] {
+            assert_eq!(
+                run(text),
+                Transition::Token(SymbolType::Identifier, Terminal::Id),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(
+            run("="),
+            Transition::Token(SymbolType::SpecialSymbol, Terminal::Equals)
+        );
+        assert_eq!(
+            run("=>"),
+            Transition::Token(SymbolType::Keyword, Terminal::Arrow)
+        );
+        assert_eq!(
+            run("<="),
+            Transition::Token(SymbolType::Keyword, Terminal::Assign)
+        );
+        assert_eq!(run("<"), Transition::Error(ErrorType::InvalidSymbol));
+        assert_eq!(run("=a"), Transition::Error(ErrorType::InvalidSymbol));
+    }
+
+    #[test]
+    fn test_constants() {
+        for text in ["0", "25", "2.5", "2.", ".5"] {
+            assert_eq!(
+                run(text),
+                Transition::Token(SymbolType::Constant, Terminal::Const),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            run("1.2.3"),
+            Transition::Error(ErrorType::ConstantHasTooManyPeriods)
+        );
+        assert_eq!(
+            run(".5.1"),
+            Transition::Error(ErrorType::ConstantHasTooManyPeriods)
+        );
+        assert_eq!(
+            run("1abc"),
+            Transition::Error(ErrorType::IdentifierBeginsWithNumber)
+        );
+        assert_eq!(run("."), Transition::Error(ErrorType::InvalidSymbol));
+    }
+
+    #[test]
+    fn test_invalid_characters() {
+        assert_eq!(run("ab$c"), Transition::Error(ErrorType::InvalidSymbol));
+        assert_eq!(run("é"), Transition::Error(ErrorType::InvalidSymbol));
+        assert_eq!(run("pack?age"), Transition::Error(ErrorType::InvalidSymbol));
+        // Part way through a keyword, the token ends straight away. Elsewhere in an identifier, the rest of the token is skipped.
+        assert_eq!(
+            DFA.next(walk("pack"), '?'),
+            &Transition::Error(ErrorType::InvalidSymbol)
+        );
+        assert!(matches!(DFA.next(walk("x"), '?'), Transition::Goto(_)));
+    }
+
+    #[test]
+    fn test_special_symbols() {
+        assert_eq!(
+            DFA.next(START, ';'),
+            &Transition::Token(SymbolType::SpecialSymbol, Terminal::Semicolon)
+        );
+        assert_eq!(
+            DFA.next(START, '#'),
+            &Transition::Token(SymbolType::SpecialSymbol, Terminal::Comment)
+        );
+    }
+
+    #[test]
+    fn test_states() {
+        assert!(DFA.transitions.iter().all(|row| row.len() == COLUMNS));
+        assert_eq!(DFA.names[START], "start");
+        // Every prefix of a keyword has a state of its own, since it is the only way to tell the keywords apart.
+        for (keyword, terminal) in KEYWORDS {
+            let state = walk(keyword);
+            assert_eq!(DFA.names[state], keyword);
+            assert_eq!(
+                DFA.outcome(state),
+                Some(&Outcome::Token(SymbolType::Keyword, terminal))
+            );
+        }
+    }
+}
@@ -0,0 +1,300 @@
+#![warn(clippy::all)]
+
+// Diagnostics are what we show the user when something is wrong with their program.
+// They know where the problem is (file, line, column and byte span), what we found, and what we would have accepted instead.
+
+use crate::error::{Error, ErrorType};
+use crate::symbol::Terminal;
+
+// A range of bytes in the source, from `start` (inclusive) to `end` (exclusive).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Span {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub(crate) file: String,
+    pub(crate) error_type: ErrorType,
+    pub(crate) token: String,
+    pub(crate) line_number: usize,
+    pub(crate) column: usize, // Counted in characters, starting at 1.
+    pub(crate) span: Span,
+    pub(crate) expected: Vec<Terminal>, // The terminals that would have been accepted. Empty for lexical errors.
+}
+
+impl Diagnostic {
+    // Create a diagnostic for an error, along with the terminals that would have been accepted instead.
+    pub fn new(file: &str, error: Error, expected: Vec<Terminal>) -> Self {
+        Diagnostic {
+            file: file.to_string(),
+            error_type: error.error_type,
+            token: error.token,
+            line_number: error.line_number,
+            column: error.column,
+            span: error.span,
+            expected,
+        }
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    pub fn error_type(&self) -> &ErrorType {
+        &self.error_type
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    // "warning" for something that does not stop the program from being accepted, and "error" for everything else.
+    pub fn severity(&self) -> &'static str {
+        if self.error_type.is_warning() {
+            "warning"
+        } else {
+            "error"
+        }
+    }
+
+    // The terminals that would have been accepted, in ascending order. Empty for lexical errors.
+    pub fn expected(&self) -> &[Terminal] {
+        &self.expected
+    }
+
+    // A readable list of the terminals that were expected, e.g. "`;` or `,`".
+    pub fn expected_to_string(&self) -> String {
+        let names: Vec<String> = self
+            .expected
+            .iter()
+            .map(|terminal| match *terminal {
+                Terminal::EndOfInput => "end of input".to_string(),
+                terminal => format!("`{}`", terminal.name()),
+            })
+            .collect();
+
+        match names.len() {
+            0 => String::new(),
+            1 => names[0].clone(),
+            n => format!("{} or {}", names[..n - 1].join(", "), names[n - 1]),
+        }
+    }
+
+    // Render the diagnostic along with the line of source that it points at, with the offending token underlined.
+    // Example:
+    //
+    // error: Syntax error at `;`
+    //   --> samples/example.ssc:3:14
+    //    |
+    //  3 |         a <= ;
+    //    |              ^ expected `[id]`, `[const]` or `(`
+    pub fn render(&self, source: &str) -> String {
+        let line_start = line_start(source, self.line_number);
+        let line: &str = source[line_start..]
+            .split('\n')
+            .next()
+            .unwrap_or("")
+            .trim_end();
+        let gutter = " ".repeat(self.line_number.to_string().len());
+
+        // The underline is at least one character wide, so that the end of the input can be pointed at too.
+        let width = source
+            .get(self.span.start..self.span.end)
+            .map_or(0, |text| text.chars().count())
+            .max(1);
+        // Tabs before the token are kept, so that the underline lines up with it however wide a tab is shown.
+        let indent: String = line
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let mut underline = format!("{}{}", indent, "^".repeat(width));
+        if !self.expected.is_empty() {
+            underline.push_str(&format!(" expected {}", self.expected_to_string()));
+        }
+
+        format!(
+            "{}: {} `{}`\n{gutter}--> {}:{}:{}\n{gutter} |\n{} | {}\n{gutter} | {}\n",
+            self.severity(),
+            self.error_type,
+            self.token,
+            self.file,
+            self.line_number,
+            self.column,
+            self.line_number,
+            line,
+            underline,
+            gutter = gutter,
+        )
+    }
+}
+
+// This tells the program how to println a diagnostic in a nice way, on a single line.
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {} `{}`",
+            self.file, self.line_number, self.column, self.error_type, self.token
+        )?;
+        if !self.expected.is_empty() {
+            write!(f, ", expected {}", self.expected_to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+// The byte offset of the first character on the given line (counting from 1).
+fn line_start(source: &str, line_number: usize) -> usize {
+    if line_number <= 1 {
+        return 0;
+    }
+
+    source
+        .match_indices('\n')
+        .nth(line_number - 2)
+        .map_or(source.len(), |(index, _)| index + 1)
+}
+
+#[cfg(test)]
+mod diagnostic_tests {
+    use crate::diagnostic::*;
+
+    fn syntax_error(token: &str, line_number: usize, column: usize, span: Span) -> Error {
+        Error {
+            error_type: ErrorType::SyntaxError,
+            token: token.to_string(),
+            line_number,
+            column,
+            span,
+        }
+    }
+
+    #[test]
+    fn test_expected_to_string() {
+        let error = syntax_error("a", 1, 1, Span::new(0, 1));
+        let diagnostic = Diagnostic::new(
+            "test.ssc",
+            error,
+            vec![Terminal::Id, Terminal::Semicolon, Terminal::EndOfInput],
+        );
+
+        assert_eq!(
+            diagnostic.expected_to_string(),
+            "`[id]`, `;` or end of input"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let src = "abstract class {\n    a <= ;\n}\n";
+        let error = syntax_error(";", 2, 10, Span::new(26, 27));
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![Terminal::Id, Terminal::Const]);
+
+        let expected = "error: Syntax error at `;`
+ --> test.ssc:2:10
+  |
+2 |     a <= ;
+  |          ^ expected `[id]` or `[const]`
+";
+
+        assert_eq!(diagnostic.render(src), expected);
+    }
+
+    #[test]
+    fn test_render_underlines_the_whole_token() {
+        let src = "val x : int;\nx <= 25.2.5;\n";
+        let error = Error {
+            error_type: ErrorType::ConstantHasTooManyPeriods,
+            token: "25.2.5".to_string(),
+            line_number: 2,
+            column: 6,
+            span: Span::new(18, 24),
+        };
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![]);
+
+        assert!(diagnostic
+            .render(src)
+            .ends_with("2 | x <= 25.2.5;\n  |      ^^^^^^\n"));
+    }
+
+    #[test]
+    fn test_render_end_of_input() {
+        let src = "package a";
+        let error = syntax_error("end of input", 1, 10, Span::new(9, 9));
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![Terminal::Semicolon]);
+
+        assert!(diagnostic
+            .render(src)
+            .ends_with("1 | package a\n  |          ^ expected `;`\n"));
+    }
+
+    #[test]
+    fn test_render_keeps_tabs() {
+        let src = "abstract class {\n\t\ta <= ;\n}\n";
+        let error = syntax_error(";", 2, 8, Span::new(24, 25));
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![]);
+
+        assert!(diagnostic
+            .render(src)
+            .ends_with("2 | \t\ta <= ;\n  | \t\t     ^\n"));
+    }
+
+    #[test]
+    fn test_render_column_zero() {
+        let src = "package a";
+        let error = syntax_error("package", 1, 0, Span::new(0, 7));
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![]);
+
+        assert!(diagnostic
+            .render(src)
+            .ends_with("1 | package a\n  | ^^^^^^^\n"));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = Error {
+            error_type: ErrorType::ConstantHasTooManyPeriods,
+            token: "25.2.5".to_string(),
+            line_number: 1,
+            column: 1,
+            span: Span::new(0, 6),
+        };
+        let diagnostic = Diagnostic::new("test.ssc", error, vec![]);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "test.ssc:1:1: Constant has too many periods `25.2.5`"
+        );
+    }
+}
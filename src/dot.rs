@@ -0,0 +1,266 @@
+#![warn(clippy::all)]
+
+// Graphviz DOT output for the two automata: the scanner's DFA and the parser's PDA. Render them with, for example, `dot -Tsvg dfa.dot -o dfa.svg`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::dfa::{Dfa, Outcome, Transition, DFA, START};
+use crate::error::ErrorType;
+use crate::grammar::{Grammar, GrammarReport};
+use crate::lexgen::describe_columns;
+use crate::pda::PDA;
+use crate::symbol::{NonTerminal, Symbol, Terminal};
+
+// The scanner's DFA.
+pub fn dfa() -> String {
+    dfa_to_dot(&DFA)
+}
+
+// The PDA of the built-in grammar, driven by PARSING_RULES and EXPANSION_RULES.
+pub fn pda() -> String {
+    pda_to_dot(&PDA::new())
+}
+
+// The PDA of another grammar, such as one loaded with `bnf::parse`. There is no PDA for a grammar that is not LL(1), so what is wrong with it is returned instead.
+pub fn pda_with_grammar(grammar: &Grammar) -> Result<String, Box<GrammarReport>> {
+    PDA::with_grammar(grammar).map(|pda| pda_to_dot(&pda))
+}
+
+// Draw a DFA. Each state is named by the shortest text that leads to it, and the states where a token can end are drawn with a double circle and labeled with what the token is.
+// Transitions are drawn on classes of characters. A separator ends the token in the state that it is in, and any character without an edge is an invalid symbol, so neither of them is drawn.
+pub fn dfa_to_dot(dfa: &Dfa) -> String {
+    let mut dot = String::from("digraph dfa {\n    rankdir=LR;\n    node [shape=circle];\n");
+    dot += "    start [shape=point];\n    start -> s0;\n";
+
+    // Tokens that end straight away, without a separator, go to a box for the token. The states that they would have gone to are never reached, so they are left out.
+    let mut reached: BTreeSet<usize> = BTreeSet::from([START]);
+    for row in &dfa.transitions {
+        for transition in row {
+            if let Transition::Goto(next) = transition {
+                reached.insert(*next);
+            }
+        }
+    }
+    let mut finished: Vec<String> = Vec::new();
+    for (state, row) in dfa.transitions.iter().enumerate() {
+        if !reached.contains(&state) {
+            continue;
+        }
+        let outcome = dfa.outcome(state);
+        match outcome {
+            Some(outcome) => {
+                dot += &format!(
+                    "    s{} [shape=doublecircle, label={}];\n",
+                    state,
+                    quote(&format!(
+                        "{}\n{}",
+                        dfa.names[state],
+                        describe_outcome(outcome)
+                    ))
+                )
+            }
+            None => dot += &format!("    s{} [label={}];\n", state, quote(&dfa.names[state])),
+        }
+
+        // Gather the columns that go to the same place, so that there is one edge per place. The edges are in the order of their first character.
+        let mut edges: Vec<(String, BTreeSet<usize>)> = Vec::new();
+        for (column, transition) in row.iter().enumerate() {
+            let target = match transition {
+                Transition::Goto(next) => format!("s{}", next),
+                Transition::Error(ErrorType::InvalidSymbol) => continue,
+                transition => {
+                    let ended = match transition {
+                        Transition::Token(symbol_type, terminal) => {
+                            Outcome::Token(symbol_type.clone(), *terminal)
+                        }
+                        Transition::Error(error_type) => Outcome::Error(error_type.clone()),
+                        Transition::Goto(_) => unreachable!(),
+                    };
+                    if outcome == Some(&ended) {
+                        continue;
+                    }
+                    let description = describe_outcome(&ended);
+                    let target = match finished.iter().position(|d| *d == description) {
+                        Some(target) => target,
+                        None => {
+                            finished.push(description);
+                            finished.len() - 1
+                        }
+                    };
+                    format!("t{}", target)
+                }
+            };
+            match edges.iter_mut().find(|(t, _)| *t == target) {
+                Some((_, columns)) => {
+                    columns.insert(column);
+                }
+                None => edges.push((target, BTreeSet::from([column]))),
+            }
+        }
+        for (target, columns) in edges {
+            dot += &format!(
+                "    s{} -> {} [label={}];\n",
+                state,
+                target,
+                quote(&describe_columns(&columns))
+            );
+        }
+    }
+
+    for (target, description) in finished.iter().enumerate() {
+        dot += &format!(
+            "    t{} [shape=box, label={}];\n",
+            target,
+            quote(description)
+        );
+    }
+    dot += "}\n";
+
+    dot
+}
+
+// Draw a PDA in the usual way for a table-driven LL(1) parser: it pushes the start symbol, then loops in its one state, and accepts once only the bottom of the stack is left.
+// In the loop, a nonterminal on top of the stack is replaced by the body of the rule that the parsing table gives for the lookahead, and a terminal on top of the stack is matched with the input.
+fn pda_to_dot(pda: &PDA) -> String {
+    let mut dot = String::from("digraph pda {\n    rankdir=LR;\n    node [shape=circle];\n");
+    dot += "    start [shape=point];\n    f [shape=doublecircle];\n    start -> p;\n";
+    dot += &format!(
+        "    p -> q [label={}];\n",
+        quote(&format!(
+            "ε, z0 / {} z0",
+            pda.symbol_name(Symbol::NonTerminal(pda.tables.start))
+        ))
+    );
+
+    // One loop for each rule, with every lookahead that the parsing table uses it for.
+    let mut lookaheads: BTreeMap<(u16, NonTerminal), Vec<Terminal>> = BTreeMap::new();
+    for ((nonterminal, lookahead), rule) in &pda.tables.parsing_rules {
+        lookaheads
+            .entry((*rule, *nonterminal))
+            .or_default()
+            .push(*lookahead);
+    }
+    for ((rule, nonterminal), mut lookahead) in lookaheads {
+        lookahead.sort_unstable();
+        let body: Vec<String> = pda.tables.expansion_rules[&rule]
+            .iter()
+            .map(|symbol| pda.symbol_name(*symbol))
+            .collect();
+        let body = if body.is_empty() {
+            "ε".to_string()
+        } else {
+            body.join(" ")
+        };
+        // The rule for the end of the input is also used when nothing else fits.
+        let names: Vec<&str> = lookahead
+            .iter()
+            .filter(|terminal| **terminal != Terminal::EndOfInput)
+            .map(|terminal| terminal.name())
+            .collect();
+        let lookahead = match (names.is_empty(), lookahead.contains(&Terminal::EndOfInput)) {
+            (true, _) => "otherwise".to_string(),
+            (false, true) => format!("on {}, or otherwise", names.join(" ")),
+            (false, false) => format!("on {}", names.join(" ")),
+        };
+        dot += &format!(
+            "    q -> q [label={}];\n",
+            quote(&format!(
+                "ε, {} / {}\n{} (rule {})",
+                pda.symbol_name(Symbol::NonTerminal(nonterminal)),
+                body,
+                lookahead,
+                rule
+            ))
+        );
+    }
+
+    let terminals: BTreeSet<Terminal> = pda
+        .tables
+        .expansion_rules
+        .values()
+        .flatten()
+        .filter_map(|symbol| match symbol {
+            Symbol::Terminal(terminal) => Some(*terminal),
+            _ => None,
+        })
+        .collect();
+    for terminal in terminals {
+        dot += &format!(
+            "    q -> q [label={}];\n",
+            quote(&format!("{0}, {0} / ε", terminal.name()))
+        );
+    }
+    dot += "    q -> f [label=\"ε, z0 / z0\"];\n}\n";
+
+    dot
+}
+
+// What a state of the DFA stands for, such as `Keyword package` or `Error: Invalid symbol`.
+fn describe_outcome(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Token(symbol_type, terminal) => format!("{} {}", symbol_type, terminal.name()),
+        Outcome::Error(error_type) => format!("Error: {}", error_type),
+    }
+}
+
+// Quote a label, escaping the characters that DOT would otherwise read as its own.
+pub(crate) fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod dot_tests {
+    use crate::dot::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+
+    #[test]
+    fn test_dfa() {
+        let dot = dfa();
+
+        assert!(dot.starts_with("digraph dfa {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    s0 [label=\"start\"];\n"));
+        // Accepting states are labeled with the kind of token.
+        assert!(dot.contains("[shape=doublecircle, label=\"package\\nKeyword package\"];"));
+        assert!(dot.contains("\\nIdentifier [id]\"];"));
+        // Special symbols end straight away.
+        assert!(dot.contains("[shape=box, label=\"Special Symbol ;\"];"));
+        assert!(dot.contains("    s0 -> t0 [label=\"#\"];\n"));
+        // The states that special symbols would have gone to are not drawn.
+        assert!(!dot.contains("label=\"#\\nSpecial Symbol #\""));
+    }
+
+    #[test]
+    fn test_pda() {
+        let dot = pda();
+
+        assert!(dot.starts_with("digraph pda {\n"));
+        assert!(dot.contains("    p -> q [label=\"ε, z0 / <scala> z0\"];\n"));
+        assert!(dot.contains("    q -> q [label=\"package, package / ε\"];\n"));
+        assert!(dot.contains("    q -> f [label=\"ε, z0 / z0\"];\n"));
+        assert!(dot.contains(
+            "    q -> q [label=\"ε, <packages> / ε\\non import abstract final sealed private protected, or otherwise (rule 3)\"];\n"
+        ));
+        // There is one loop for each rule, and one for each terminal.
+        let loops = dot.matches("q -> q").count();
+        assert_eq!(
+            loops,
+            crate::rules::EXPANSION_RULES.len() + Terminal::ALL.len()
+        );
+    }
+}
@@ -1,4 +1,8 @@
 #![warn(clippy::all)]
+
+use crate::ast::Type;
+use crate::diagnostic::Span;
+
 // Keeping track of a few types of errors
 #[derive(Debug, Clone, PartialEq)]
 pub enum ErrorType {
@@ -6,11 +10,98 @@ pub enum ErrorType {
     ConstantHasTooManyPeriods,
     IdentifierBeginsWithNumber,
     // Just a general placeholder syntax error
-    // SyntaxError,
+    SyntaxError,
+    // Found by the semantic analysis, after the program has been parsed.
+    UndeclaredIdentifier,
+    DuplicateDeclaration,
+    ShadowedDeclaration, // Only a warning.
+    AssignmentMismatch { target: Type, value: Type },
+    NonBooleanCondition(Type),
+    BooleanOperand, // A bool where `+`, `*` or `@` needs a number.
+}
+
+impl ErrorType {
+    // Whether this is only a warning, which does not stop a program from being accepted.
+    pub fn is_warning(&self) -> bool {
+        *self == ErrorType::ShadowedDeclaration
+    }
+
+    // Whether this was found by the semantic analysis, rather than by the scanner or the parser.
+    pub fn is_semantic(&self) -> bool {
+        matches!(
+            *self,
+            ErrorType::UndeclaredIdentifier
+                | ErrorType::DuplicateDeclaration
+                | ErrorType::ShadowedDeclaration
+                | ErrorType::AssignmentMismatch { .. }
+                | ErrorType::NonBooleanCondition(_)
+                | ErrorType::BooleanOperand
+        )
+    }
+}
+
+// This tells the program how to println an ErrorType in a nice way.
+impl std::fmt::Display for ErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            ErrorType::AssignmentMismatch { target, value } => {
+                return write!(f, "Cannot assign {} to {} variable", value, target)
+            }
+            ErrorType::NonBooleanCondition(found) => {
+                return write!(f, "Condition is {} instead of bool at", found)
+            }
+            ErrorType::InvalidSymbol => "Invalid symbol",
+            ErrorType::ConstantHasTooManyPeriods => "Constant has too many periods",
+            ErrorType::IdentifierBeginsWithNumber => "Identifier begins with a number",
+            ErrorType::SyntaxError => "Syntax error at",
+            ErrorType::UndeclaredIdentifier => "Undeclared identifier",
+            ErrorType::DuplicateDeclaration => "Duplicate declaration of",
+            ErrorType::ShadowedDeclaration => "Declaration shadows",
+            ErrorType::BooleanOperand => "Arithmetic on a bool",
+        };
+
+        write!(f, "{}", printable)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Error {
     pub(crate) error_type: ErrorType,
     pub(crate) token: String,
+    pub(crate) line_number: usize,
+    pub(crate) column: usize,
+    pub(crate) span: Span,
+}
+
+impl Error {
+    pub fn error_type(&self) -> &ErrorType {
+        &self.error_type
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+// This tells the program how to println an error in a nice way.
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Line {}: {} `{}`",
+            self.line_number, self.error_type, self.token
+        )
+    }
 }
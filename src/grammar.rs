@@ -0,0 +1,898 @@
+#![warn(clippy::all)]
+
+// Grammar analysis: nullable nonterminals, FIRST(1) and FOLLOW(1) sets, and the LL(1) parsing table, all computed from the productions.
+// The hand-written sets and table in rules.rs are checked against these in the tests below.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::rules::{EXPANSION_RULES, RULE_HEADS, START_SYMBOL};
+use crate::symbol::{NonTerminal, Symbol, Terminal};
+
+// A single production: the nonterminal that it expands, and what it expands to. An empty body is an epsilon production.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Production {
+    pub(crate) head: NonTerminal,
+    pub(crate) body: Vec<Symbol>,
+}
+
+// A context-free grammar, with numbered productions.
+#[derive(Clone, Debug)]
+pub struct Grammar {
+    pub(crate) start: NonTerminal,
+    pub(crate) productions: BTreeMap<u16, Production>,
+    pub(crate) names: BTreeMap<NonTerminal, String>, // The names of any nonterminals that are not in the built-in grammar, such as those from a grammar file.
+}
+
+// The results of analyzing a grammar.
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub(crate) nullable: BTreeSet<NonTerminal>,
+    pub(crate) first: BTreeMap<NonTerminal, BTreeSet<Terminal>>,
+    pub(crate) follow: BTreeMap<NonTerminal, BTreeSet<Terminal>>,
+}
+
+impl Production {
+    pub fn head(&self) -> NonTerminal {
+        self.head
+    }
+
+    pub fn body(&self) -> &[Symbol] {
+        &self.body
+    }
+}
+
+impl Grammar {
+    // The grammar of Simple Scala, as given by EXPANSION_RULES and RULE_HEADS.
+    pub fn builtin() -> Self {
+        let productions = EXPANSION_RULES
+            .iter()
+            .map(|(rule, body)| {
+                let production = Production {
+                    head: *RULE_HEADS.get(rule).unwrap(),
+                    body: body.clone(),
+                };
+                (*rule, production)
+            })
+            .collect();
+
+        Grammar {
+            start: START_SYMBOL,
+            productions,
+            names: BTreeMap::new(),
+        }
+    }
+
+    pub fn start(&self) -> NonTerminal {
+        self.start
+    }
+
+    // Every production, by rule number, in ascending order.
+    pub fn productions(&self) -> impl Iterator<Item = (u16, &Production)> {
+        self.productions
+            .iter()
+            .map(|(rule, production)| (*rule, production))
+    }
+
+    // Return the string form of a symbol, including the names of the grammar's own nonterminals.
+    pub fn symbol_to_string(&self, symbol: Symbol) -> String {
+        symbol_to_string(&self.names, symbol)
+    }
+
+    // Every nonterminal in the grammar, in ascending order.
+    pub fn nonterminals(&self) -> BTreeSet<NonTerminal> {
+        self.productions.values().map(|p| p.head).collect()
+    }
+
+    // Every terminal that appears in a production, in ascending order.
+    pub fn terminals(&self) -> BTreeSet<Terminal> {
+        self.productions
+            .values()
+            .flat_map(|p| p.body.iter())
+            .filter_map(|symbol| match symbol {
+                Symbol::Terminal(terminal) => Some(*terminal),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Compute the nullable nonterminals, then the FIRST(1) and FOLLOW(1) sets of every nonterminal.
+    // Each of these is a fixed point: we keep going over the productions until nothing changes.
+    pub fn analyze(&self) -> Analysis {
+        let nonterminals = self.nonterminals();
+
+        // Nullable nonterminals.
+        let mut nullable = BTreeSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions.values() {
+                if !nullable.contains(&production.head)
+                    && production.body.iter().all(|symbol| match symbol {
+                        Symbol::NonTerminal(nonterminal) => nullable.contains(nonterminal),
+                        _ => false,
+                    })
+                {
+                    nullable.insert(production.head);
+                    changed = true;
+                }
+            }
+        }
+
+        // FIRST(1) sets. These never contain epsilon; whether a nonterminal derives epsilon is what `nullable` is for.
+        let mut first: BTreeMap<NonTerminal, BTreeSet<Terminal>> = nonterminals
+            .iter()
+            .map(|nonterminal| (*nonterminal, BTreeSet::new()))
+            .collect();
+        changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions.values() {
+                let (body_first, _) = first_of_sequence(&production.body, &first, &nullable);
+                let head_first = first.get_mut(&production.head).unwrap();
+                for terminal in body_first {
+                    changed |= head_first.insert(terminal);
+                }
+            }
+        }
+
+        // FOLLOW(1) sets.
+        let mut follow: BTreeMap<NonTerminal, BTreeSet<Terminal>> = nonterminals
+            .iter()
+            .map(|nonterminal| (*nonterminal, BTreeSet::new()))
+            .collect();
+        follow
+            .get_mut(&self.start)
+            .unwrap()
+            .insert(Terminal::EndOfInput);
+        changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions.values() {
+                for (index, symbol) in production.body.iter().enumerate() {
+                    let nonterminal = match symbol {
+                        Symbol::NonTerminal(nonterminal) => nonterminal,
+                        _ => continue,
+                    };
+
+                    // Whatever can begin the rest of the body can follow this symbol, and if the rest of the body can vanish, so can whatever follows the head.
+                    let rest = &production.body[index + 1..];
+                    let (mut symbol_follow, rest_is_nullable) =
+                        first_of_sequence(rest, &first, &nullable);
+                    if rest_is_nullable {
+                        symbol_follow.extend(follow.get(&production.head).unwrap().iter().copied());
+                    }
+
+                    let entry = follow.get_mut(nonterminal).unwrap();
+                    for terminal in symbol_follow {
+                        changed |= entry.insert(terminal);
+                    }
+                }
+            }
+        }
+
+        Analysis {
+            nullable,
+            first,
+            follow,
+        }
+    }
+
+    // The predictive parsing table, keyed on (nonterminal, lookahead). Every rule that could be chosen is listed, so an entry with more than one rule is a conflict.
+    pub fn predictions(
+        &self,
+        analysis: &Analysis,
+    ) -> BTreeMap<(NonTerminal, Terminal), BTreeSet<u16>> {
+        let mut table: BTreeMap<(NonTerminal, Terminal), BTreeSet<u16>> = BTreeMap::new();
+
+        for (rule, production) in self.productions.iter() {
+            for lookahead in analysis.predict_set(production) {
+                table
+                    .entry((production.head, lookahead))
+                    .or_default()
+                    .insert(*rule);
+            }
+        }
+
+        table
+    }
+
+    // The LL(1) parsing table, in the same form as PARSING_RULES. If there is a conflict, the lowest-numbered rule wins.
+    pub fn parsing_table(&self) -> HashMap<(NonTerminal, Terminal), u16> {
+        let analysis = self.analyze();
+
+        self.predictions(&analysis)
+            .into_iter()
+            .map(|(key, rules)| (key, *rules.iter().next().unwrap()))
+            .collect()
+    }
+
+    // Print the LL(1) parsing table, one cell per line, in the order of the nonterminals and then the lookaheads.
+    pub fn table_to_string(&self) -> String {
+        let mut cells: Vec<((NonTerminal, Terminal), u16)> =
+            self.parsing_table().into_iter().collect();
+        cells.sort_unstable();
+
+        let mut table = format!(
+            "{0: <30} | {1: <30} | {2: <}\n",
+            "Nonterminal", "Lookahead", "Rule"
+        );
+        for ((nonterminal, terminal), rule) in cells {
+            table += &format!(
+                "{0: <30} | {1: <30} | {2: <}\n",
+                self.symbol_name(nonterminal.into()),
+                terminal.name(),
+                rule
+            );
+        }
+
+        table
+    }
+
+    // The name of a symbol as it is written in a grammar file.
+    fn symbol_name(&self, symbol: Symbol) -> String {
+        symbol.name_in(&self.names)
+    }
+}
+
+// This tells the program how to println a grammar in a nice way, in the same BNF that `bnf::parse` reads. Each alternative is followed by its rule number as a comment.
+impl std::fmt::Display for Grammar {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut previous: Option<NonTerminal> = None;
+        for (rule, production) in self.productions() {
+            let head = self.symbol_name(production.head.into());
+            let body: Vec<String> = production
+                .body
+                .iter()
+                .map(|symbol| match symbol {
+                    // A terminal is quoted with whichever quote is not in its name.
+                    Symbol::Terminal(terminal) if terminal.name().contains('"') => {
+                        format!("'{}'", terminal.name())
+                    }
+                    Symbol::Terminal(terminal) => format!("\"{}\"", terminal.name()),
+                    symbol => self.symbol_name(*symbol),
+                })
+                .collect();
+            let body = if body.is_empty() {
+                "ε".to_string()
+            } else {
+                body.join(" ")
+            };
+
+            // Productions for the same nonterminal are written as alternatives of one rule.
+            let line = if previous == Some(production.head) {
+                format!("{} | {}", " ".repeat(head.chars().count()), body)
+            } else {
+                if previous.is_some() {
+                    writeln!(f)?;
+                }
+                format!("{} ::= {}", head, body)
+            };
+            writeln!(f, "{: <72} # {}", line, rule)?;
+            previous = Some(production.head);
+        }
+
+        Ok(())
+    }
+}
+
+// The two kinds of LL(1) conflict.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConflictKind {
+    // Two rules for the same nonterminal can both begin with the terminal.
+    FirstFirst,
+    // One rule can begin with the terminal, and another can vanish and be followed by it.
+    FirstFollow,
+}
+
+// This tells the program how to println a ConflictKind in a nice way.
+impl std::fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            ConflictKind::FirstFirst => "FIRST/FIRST",
+            ConflictKind::FirstFollow => "FIRST/FOLLOW",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+// A cell of the parsing table that more than one rule wants.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Conflict {
+    pub(crate) kind: ConflictKind,
+    pub(crate) nonterminal: NonTerminal,
+    pub(crate) terminal: Terminal,
+    pub(crate) rules: Vec<u16>,
+}
+
+// Everything that `Grammar::check` found wrong with a grammar.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GrammarReport {
+    pub(crate) conflicts: Vec<Conflict>,
+    pub(crate) left_recursion: Vec<Vec<NonTerminal>>, // Each entry is a cycle, beginning and ending with the same nonterminal.
+    pub(crate) unreachable: Vec<NonTerminal>,
+    pub(crate) unproductive: Vec<NonTerminal>,
+    pub(crate) unused_terminals: Vec<Terminal>, // Not a problem: a grammar does not have to use every terminal that the scanner knows.
+    names: BTreeMap<NonTerminal, String>, // The names of the grammar's own nonterminals, for printing.
+}
+
+impl GrammarReport {
+    // Whether the grammar is fit for our LL(1) parser. Unused terminals do not count against it.
+    pub fn is_ok(&self) -> bool {
+        self.conflicts.is_empty()
+            && self.left_recursion.is_empty()
+            && self.unreachable.is_empty()
+            && self.unproductive.is_empty()
+    }
+
+    // Whether the grammar can drive our LL(1) parser at all. Unreachable and unproductive nonterminals are dead weight, but conflicts and left recursion make the parser choose the wrong rule, or choose the same one forever.
+    pub fn is_ll1(&self) -> bool {
+        self.conflicts.is_empty() && self.left_recursion.is_empty()
+    }
+
+    fn symbol_to_string(&self, symbol: impl Into<Symbol>) -> String {
+        symbol_to_string(&self.names, symbol.into())
+    }
+}
+
+// This tells the program how to println a grammar report in a nice way, one problem per line.
+impl std::fmt::Display for GrammarReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_ok() {
+            writeln!(f, "No problems found. The grammar is LL(1).")?;
+        }
+
+        for conflict in self.conflicts.iter() {
+            let rules: Vec<String> = conflict.rules.iter().map(|r| r.to_string()).collect();
+            writeln!(
+                f,
+                "{} conflict: {} on {}, between rules {}",
+                conflict.kind,
+                self.symbol_to_string(conflict.nonterminal),
+                self.symbol_to_string(conflict.terminal),
+                rules.join(", ")
+            )?;
+        }
+        for cycle in self.left_recursion.iter() {
+            let path: Vec<String> = cycle
+                .iter()
+                .map(|code| self.symbol_to_string(*code))
+                .collect();
+            writeln!(f, "Left recursion: {}", path.join(" -> "))?;
+        }
+        for nonterminal in self.unreachable.iter() {
+            writeln!(
+                f,
+                "Unreachable nonterminal: {}",
+                self.symbol_to_string(*nonterminal)
+            )?;
+        }
+        for nonterminal in self.unproductive.iter() {
+            writeln!(
+                f,
+                "Unproductive nonterminal: {}",
+                self.symbol_to_string(*nonterminal)
+            )?;
+        }
+        if !self.unused_terminals.is_empty() {
+            let terminals: Vec<String> = self
+                .unused_terminals
+                .iter()
+                .map(|terminal| self.symbol_to_string(*terminal))
+                .collect();
+            writeln!(f, "Terminals that are not used: {}", terminals.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Grammar {
+    // Check that the grammar can be used by our LL(1) parser, and that it has no dead weight.
+    pub fn check(&self) -> GrammarReport {
+        self.check_with(&self.analyze())
+    }
+
+    // The same as `check`, for a grammar that has already been analyzed.
+    pub(crate) fn check_with(&self, analysis: &Analysis) -> GrammarReport {
+        GrammarReport {
+            conflicts: self.conflicts(analysis),
+            left_recursion: self.left_recursion(analysis),
+            unreachable: self.unreachable(),
+            unproductive: self.unproductive(),
+            unused_terminals: self.unused_terminals(),
+            names: self.names.clone(),
+        }
+    }
+
+    fn conflicts(&self, analysis: &Analysis) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+
+        for ((nonterminal, terminal), rules) in self.predictions(analysis) {
+            if rules.len() < 2 {
+                continue;
+            }
+
+            // If at least two of the rules get the terminal from their own FIRST sets, they clash directly. Otherwise, the clash comes from FOLLOW.
+            let from_first = rules
+                .iter()
+                .filter(|rule| {
+                    let (first, _) = analysis.first_of(&self.productions[rule].body);
+                    first.contains(&terminal)
+                })
+                .count();
+            let kind = if from_first >= 2 {
+                ConflictKind::FirstFirst
+            } else {
+                ConflictKind::FirstFollow
+            };
+
+            conflicts.push(Conflict {
+                kind,
+                nonterminal,
+                terminal,
+                rules: rules.into_iter().collect(),
+            });
+        }
+
+        conflicts
+    }
+
+    // Find every cycle A -> ... -> A, where each step is to a nonterminal that can begin the body of a rule for the previous one.
+    fn left_recursion(&self, analysis: &Analysis) -> Vec<Vec<NonTerminal>> {
+        // The nonterminals that may be leftmost in a rule for each nonterminal.
+        let mut leftmost: BTreeMap<NonTerminal, BTreeSet<NonTerminal>> = BTreeMap::new();
+        for production in self.productions.values() {
+            let edges = leftmost.entry(production.head).or_default();
+            for symbol in production.body.iter() {
+                let nonterminal = match symbol {
+                    Symbol::NonTerminal(nonterminal) => *nonterminal,
+                    _ => break,
+                };
+                edges.insert(nonterminal);
+                if !analysis.nullable.contains(&nonterminal) {
+                    break;
+                }
+            }
+        }
+
+        let mut cycles: Vec<Vec<NonTerminal>> = Vec::new();
+        for start in self.nonterminals() {
+            // Mutual recursion only needs to be reported once.
+            if cycles.iter().any(|cycle| cycle.contains(&start)) {
+                continue;
+            }
+
+            // A breadth-first search for the shortest way back to where we started.
+            let mut came_from: BTreeMap<NonTerminal, NonTerminal> = BTreeMap::new();
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                let next_symbols = leftmost.get(&current).cloned().unwrap_or_default();
+                if next_symbols.contains(&start) {
+                    let mut cycle = vec![start, current];
+                    let mut step = current;
+                    while step != start {
+                        step = came_from[&step];
+                        cycle.push(step);
+                    }
+                    cycle.reverse();
+                    cycle.dedup();
+                    if cycle.len() == 1 {
+                        cycle.push(start);
+                    }
+                    cycles.push(cycle);
+                    break;
+                }
+                for next in next_symbols {
+                    if next != start && !came_from.contains_key(&next) {
+                        came_from.insert(next, current);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        cycles
+    }
+
+    // The nonterminals that no derivation from the start symbol can reach.
+    fn unreachable(&self) -> Vec<NonTerminal> {
+        let mut reached = BTreeSet::from([self.start]);
+        let mut stack = vec![self.start];
+        while let Some(current) = stack.pop() {
+            for production in self.productions.values().filter(|p| p.head == current) {
+                for symbol in production.body.iter() {
+                    if let Symbol::NonTerminal(nonterminal) = symbol {
+                        if reached.insert(*nonterminal) {
+                            stack.push(*nonterminal);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.nonterminals().difference(&reached).copied().collect()
+    }
+
+    // The nonterminals that can never derive a string made only of terminals.
+    fn unproductive(&self) -> Vec<NonTerminal> {
+        let mut productive = BTreeSet::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in self.productions.values() {
+                if !productive.contains(&production.head)
+                    && production.body.iter().all(|symbol| match symbol {
+                        Symbol::NonTerminal(nonterminal) => productive.contains(nonterminal),
+                        _ => true,
+                    })
+                {
+                    productive.insert(production.head);
+                    changed = true;
+                }
+            }
+        }
+
+        self.nonterminals()
+            .difference(&productive)
+            .copied()
+            .collect()
+    }
+
+    // The terminals that the scanner can produce, but that no rule uses.
+    fn unused_terminals(&self) -> Vec<Terminal> {
+        let used = self.terminals();
+
+        Terminal::ALL
+            .into_iter()
+            .filter(|terminal| !used.contains(terminal))
+            .collect()
+    }
+}
+
+// The string form of a symbol, using the given names for any nonterminals that are not in the built-in grammar.
+pub(crate) fn symbol_to_string(names: &BTreeMap<NonTerminal, String>, symbol: Symbol) -> String {
+    format!("{} ({})", symbol.name_in(names), symbol.code())
+}
+
+impl Analysis {
+    // Whether the nonterminal can derive epsilon.
+    pub fn is_nullable(&self, nonterminal: NonTerminal) -> bool {
+        self.nullable.contains(&nonterminal)
+    }
+
+    // The FIRST(1) set of a nonterminal, if it is in the grammar.
+    pub fn first(&self, nonterminal: NonTerminal) -> Option<&BTreeSet<Terminal>> {
+        self.first.get(&nonterminal)
+    }
+
+    // The FOLLOW(1) set of a nonterminal, if it is in the grammar.
+    pub fn follow(&self, nonterminal: NonTerminal) -> Option<&BTreeSet<Terminal>> {
+        self.follow.get(&nonterminal)
+    }
+
+    // The lookaheads for which a production should be chosen: FIRST of its body, plus FOLLOW of its head if the body can vanish.
+    pub fn predict_set(&self, production: &Production) -> BTreeSet<Terminal> {
+        let (mut predict, body_is_nullable) =
+            first_of_sequence(&production.body, &self.first, &self.nullable);
+        if body_is_nullable {
+            predict.extend(self.follow.get(&production.head).unwrap().iter().copied());
+        }
+
+        predict
+    }
+
+    // FIRST of a sequence of symbols, and whether the whole sequence can vanish.
+    pub fn first_of(&self, symbols: &[Symbol]) -> (BTreeSet<Terminal>, bool) {
+        first_of_sequence(symbols, &self.first, &self.nullable)
+    }
+}
+
+// FIRST of a sequence of symbols, given the FIRST sets computed so far, and whether the whole sequence is nullable.
+fn first_of_sequence(
+    symbols: &[Symbol],
+    first: &BTreeMap<NonTerminal, BTreeSet<Terminal>>,
+    nullable: &BTreeSet<NonTerminal>,
+) -> (BTreeSet<Terminal>, bool) {
+    let mut ret = BTreeSet::new();
+
+    for symbol in symbols {
+        match symbol {
+            // A nonterminal: take its FIRST set, and keep going only if it can vanish.
+            Symbol::NonTerminal(nonterminal) => {
+                ret.extend(first[nonterminal].iter().copied());
+                if !nullable.contains(nonterminal) {
+                    return (ret, false);
+                }
+            }
+            // A terminal is its own FIRST set, and never vanishes.
+            Symbol::Terminal(terminal) => {
+                ret.insert(*terminal);
+                return (ret, false);
+            }
+            Symbol::Bottom => return (ret, false),
+        }
+    }
+
+    (ret, true)
+}
+
+#[cfg(test)]
+mod grammar_tests {
+    use crate::grammar::*;
+    use crate::rules::{
+        FIRST_MODIFIER, FIRST_SCALA, FIRST_STATEMENT, FOLLOW_ARITH, FOLLOW_IMPORTS,
+        FOLLOW_PACKAGES, FOLLOW_SETS, PARSING_RULES,
+    };
+    use std::collections::HashSet;
+
+    fn to_hash_set(set: &BTreeSet<Terminal>) -> HashSet<Terminal> {
+        set.iter().copied().collect()
+    }
+
+    // The nonterminal with the given legacy code.
+    fn nt(code: u16) -> NonTerminal {
+        NonTerminal::new(code).unwrap()
+    }
+
+    #[test]
+    fn test_nullable() {
+        let analysis = Grammar::builtin().analyze();
+
+        let expected: BTreeSet<NonTerminal> = [42, 43, 44, 45, 51, 56, 67, 69]
+            .into_iter()
+            .map(nt)
+            .collect();
+        assert_eq!(analysis.nullable, expected);
+    }
+
+    #[test]
+    fn test_first_sets_match_the_hand_written_ones() {
+        let analysis = Grammar::builtin().analyze();
+
+        // FIRST_SCALA uses epsilon to say that <scala> is nullable.
+        let mut first_scala = to_hash_set(&analysis.first[&NonTerminal::SCALA]);
+        first_scala.insert(Terminal::EndOfInput);
+        assert_eq!(first_scala, *FIRST_SCALA);
+
+        assert_eq!(
+            to_hash_set(&analysis.first[&NonTerminal::MODIFIER]),
+            *FIRST_MODIFIER
+        );
+        assert_eq!(
+            to_hash_set(&analysis.first[&NonTerminal::STMT]),
+            *FIRST_STATEMENT
+        );
+    }
+
+    #[test]
+    fn test_follow_sets_match_the_hand_written_ones() {
+        let analysis = Grammar::builtin().analyze();
+
+        assert_eq!(
+            to_hash_set(&analysis.follow[&NonTerminal::PACKAGES]),
+            *FOLLOW_PACKAGES
+        );
+        assert_eq!(
+            to_hash_set(&analysis.follow[&NonTerminal::IMPORTS]),
+            *FOLLOW_IMPORTS
+        );
+        assert_eq!(
+            to_hash_set(&analysis.follow[&NonTerminal::ARITH]),
+            *FOLLOW_ARITH
+        );
+
+        for (nonterminal, follow) in FOLLOW_SETS.iter() {
+            assert_eq!(
+                &to_hash_set(&analysis.follow[nonterminal]),
+                follow,
+                "FOLLOW({}) differs",
+                nonterminal
+            );
+        }
+    }
+
+    #[test]
+    fn test_grammar_is_ll1() {
+        let grammar = Grammar::builtin();
+        let analysis = grammar.analyze();
+
+        for (key, rules) in grammar.predictions(&analysis) {
+            assert_eq!(rules.len(), 1, "conflict at {:?}: {:?}", key, rules);
+        }
+    }
+
+    // The hand-written table falls back to a nonterminal's epsilon entry when there is no entry for the lookahead, so it only lists the epsilon entry for some epsilon rules.
+    // Every computed entry has to agree with that lookup, and every hand-written entry has to be in the computed table, other than those fallbacks.
+    #[test]
+    fn test_parsing_table_matches_the_hand_written_one() {
+        let grammar = Grammar::builtin();
+        let analysis = grammar.analyze();
+        let table = grammar.parsing_table();
+
+        for ((nonterminal, lookahead), rule) in table.iter() {
+            let hand_written = PARSING_RULES
+                .get(&(*nonterminal, *lookahead))
+                .or_else(|| PARSING_RULES.get(&(*nonterminal, Terminal::EndOfInput)));
+            assert_eq!(
+                hand_written,
+                Some(rule),
+                "entry ({}, {}) differs",
+                nonterminal,
+                lookahead
+            );
+        }
+
+        for ((nonterminal, lookahead), rule) in PARSING_RULES.iter() {
+            if *lookahead == Terminal::EndOfInput
+                && !analysis.follow[nonterminal].contains(&Terminal::EndOfInput)
+            {
+                assert!(grammar.productions[rule].body.is_empty());
+            } else {
+                assert_eq!(table.get(&(*nonterminal, *lookahead)), Some(rule));
+            }
+        }
+    }
+
+    // Build a grammar from (rule, head, body) triples, given in legacy codes.
+    fn grammar(start: u16, productions: &[(u16, u16, &[u16])]) -> Grammar {
+        Grammar {
+            start: nt(start),
+            productions: productions
+                .iter()
+                .map(|(rule, head, body)| {
+                    let production = Production {
+                        head: nt(*head),
+                        body: body
+                            .iter()
+                            .map(|code| Symbol::from_code(*code).unwrap())
+                            .collect(),
+                    };
+                    (*rule, production)
+                })
+                .collect(),
+            names: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_builtin_grammar_passes_the_check() {
+        let report = Grammar::builtin().check();
+
+        assert!(report.is_ok(), "{}", report);
+    }
+
+    #[test]
+    fn test_first_first_conflict() {
+        // <a> -> [id] ; | [id] ,
+        let report = grammar(70, &[(1, 70, &[1, 31]), (2, 70, &[1, 37])]).check();
+
+        assert_eq!(
+            report.conflicts,
+            vec![Conflict {
+                kind: ConflictKind::FirstFirst,
+                nonterminal: nt(70),
+                terminal: Terminal::Id,
+                rules: vec![1, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_first_follow_conflict() {
+        // <a> -> <b> [id]
+        // <b> -> [id] | epsilon
+        let report = grammar(70, &[(1, 70, &[71, 1]), (2, 71, &[1]), (3, 71, &[])]).check();
+
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].kind, ConflictKind::FirstFollow);
+        assert_eq!(report.conflicts[0].nonterminal, nt(71));
+        assert_eq!(report.conflicts[0].terminal, Terminal::Id);
+        assert_eq!(report.conflicts[0].rules, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_left_recursion() {
+        // <a> -> <a> + [id] | [id]
+        // <b> -> <c> ; and <c> -> <b> ,
+        let report = grammar(
+            70,
+            &[
+                (1, 70, &[70, 39, 1]),
+                (2, 70, &[1, 71]),
+                (3, 71, &[72, 31]),
+                (4, 72, &[71, 37]),
+            ],
+        )
+        .check();
+
+        assert_eq!(
+            report.left_recursion,
+            vec![vec![nt(70), nt(70)], vec![nt(71), nt(72), nt(71)]]
+        );
+        assert!(!report.is_ll1());
+    }
+
+    #[test]
+    fn test_unreachable_and_unproductive_nonterminals() {
+        // <a> -> [id] <b> ; | [const]
+        // <b> -> ( <b> )
+        // <c> -> [id]
+        let report = grammar(
+            70,
+            &[
+                (1, 70, &[1, 71, 31]),
+                (2, 70, &[2]),
+                (3, 71, &[34, 71, 35]),
+                (4, 72, &[1]),
+            ],
+        )
+        .check();
+
+        assert_eq!(report.unreachable, vec![nt(72)]);
+        assert_eq!(report.unproductive, vec![nt(71)]);
+        assert!(!report.is_ok());
+        assert!(report.is_ll1());
+    }
+
+    #[test]
+    fn test_unused_terminals() {
+        let report = grammar(70, &[(1, 70, &(2..=41).collect::<Vec<u16>>())]).check();
+
+        assert_eq!(report.unused_terminals, vec![Terminal::Id]);
+        assert!(report.is_ok());
+        assert_eq!(
+            report.to_string(),
+            "No problems found. The grammar is LL(1).\nTerminals that are not used: [id] (1)\n"
+        );
+    }
+
+    #[test]
+    fn test_first_of_sequence() {
+        let analysis = Grammar::builtin().analyze();
+
+        // <packages> <imports> can vanish, and can begin with `package` or `import`.
+        let (first, nullable) =
+            analysis.first_of(&[NonTerminal::PACKAGES.into(), NonTerminal::IMPORTS.into()]);
+        assert_eq!(
+            first,
+            [Terminal::Package, Terminal::Import].into_iter().collect()
+        );
+        assert!(nullable);
+
+        // <more-ids> ) cannot vanish.
+        let (first, nullable) =
+            analysis.first_of(&[NonTerminal::MORE_IDS.into(), Terminal::RightParen.into()]);
+        assert_eq!(
+            first,
+            [Terminal::RightParen, Terminal::Comma]
+                .into_iter()
+                .collect()
+        );
+        assert!(!nullable);
+    }
+
+    #[test]
+    fn test_display_round_trips_through_bnf() {
+        let grammar = Grammar::builtin();
+        let text = grammar.to_string();
+
+        assert!(text.starts_with("<scala> ::= <packages> <imports> <scala-body>"));
+        assert!(text.contains("\n<packages> ::= \"package\" \"[id]\" \";\" <packages>"));
+        assert!(text.contains("\n           | ε "));
+        let loaded = crate::bnf::parse(&text).unwrap();
+        assert_eq!(loaded.start(), grammar.start());
+        assert!(loaded.productions().eq(grammar.productions()));
+    }
+
+    #[test]
+    fn test_table_to_string() {
+        let table = Grammar::builtin().table_to_string();
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert!(lines[0].starts_with("Nonterminal "));
+        assert_eq!(lines.len(), Grammar::builtin().parsing_table().len() + 1);
+        assert!(lines
+            .contains(&format!("{0: <30} | {1: <30} | {2: <}", "<scala>", "package", 1).as_str()));
+    }
+}
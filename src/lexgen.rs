@@ -0,0 +1,637 @@
+#![warn(clippy::all)]
+
+// The lexer generator: it turns a list of regular expressions into a minimal DFA that recognizes all of them.
+// This is done in three steps, as in the textbook: Thompson's construction of an NFA for each regex, the subset construction of a DFA from the NFAs, and then minimization of the DFA by partition refinement.
+// What the tokens are, and what the scanner does with the DFA, is up to dfa.rs.
+//
+// The regex syntax is the usual one, with a few escapes of our own:
+// - `a|b`, `ab`, `a*`, `a+`, `a?` and `(a)`
+// - `.` for any character, and `[abc]`, `[a-z]` or `[^abc]` for a class of characters
+// - `\s` for a separator (whitespace or a special symbol), `\S` for anything else, `\d` for a digit and `\a` for a letter, including non-ASCII letters
+// - `\` before any other character matches that character, as in `\.` or `\*`
+//
+// Every ASCII character has a column of its own in the transition table, but the rest of Unicode only has three: separators, letters and everything else.
+// A non-ASCII character in a regex stands for its whole column.
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::scanner::is_separator;
+
+// The columns for the rest of Unicode, after the 128 for ASCII.
+const NON_ASCII_SEPARATOR: usize = 128;
+const NON_ASCII_LETTER: usize = 129;
+const NON_ASCII_OTHER: usize = 130;
+pub const COLUMNS: usize = 131;
+
+// The column of the transition table for a character.
+pub fn column(c: char) -> usize {
+    if c.is_ascii() {
+        c as usize
+    } else if is_separator(c) {
+        NON_ASCII_SEPARATOR
+    } else if c.is_alphabetic() {
+        NON_ASCII_LETTER
+    } else {
+        NON_ASCII_OTHER
+    }
+}
+
+// A character that stands for the column: the character itself for ASCII, or one of the class for the rest.
+pub fn representative(column: usize) -> char {
+    match column {
+        NON_ASCII_SEPARATOR => '\u{a0}',
+        NON_ASCII_LETTER => 'é',
+        NON_ASCII_OTHER => '€',
+        column => column as u8 as char,
+    }
+}
+
+// Describe a set of columns as a character class, such as `[0-9a-z]`, for a person to read. A large set is described by what it leaves out, such as `[^;{}]`.
+pub fn describe_columns(columns: &BTreeSet<usize>) -> String {
+    if columns.len() == 1 {
+        return describe_column(*columns.iter().next().unwrap());
+    }
+    if columns.len() > COLUMNS / 2 {
+        let rest: BTreeSet<usize> = (0..COLUMNS).filter(|c| !columns.contains(c)).collect();
+        if rest.is_empty() {
+            return "any".to_string();
+        }
+        return format!("[^{}]", describe_ranges(&rest));
+    }
+
+    format!("[{}]", describe_ranges(columns))
+}
+
+// The columns inside the brackets of a class, with runs of three or more written as ranges.
+fn describe_ranges(columns: &BTreeSet<usize>) -> String {
+    let columns: Vec<usize> = columns.iter().copied().collect();
+    let mut text = String::new();
+    let mut start = 0;
+    while start < columns.len() {
+        let mut end = start;
+        while end + 1 < columns.len()
+            && columns[end + 1] == columns[end] + 1
+            && columns[end + 1] < 128
+        {
+            end += 1;
+        }
+        if end - start >= 2 {
+            text += &format!(
+                "{}-{}",
+                describe_column(columns[start]),
+                describe_column(columns[end])
+            );
+        } else {
+            for column in &columns[start..=end] {
+                text += &describe_column(*column);
+            }
+        }
+        start = end + 1;
+    }
+
+    text
+}
+
+fn describe_column(column: usize) -> String {
+    match column {
+        NON_ASCII_SEPARATOR => "<non-ASCII space>".to_string(),
+        NON_ASCII_LETTER => "<non-ASCII letter>".to_string(),
+        NON_ASCII_OTHER => "<non-ASCII symbol>".to_string(),
+        column => match column as u8 as char {
+            '\t' => "\\t".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            c @ ('\\' | '[' | ']' | '^' | '-') => format!("\\{}", c),
+            c if c.is_ascii_graphic() => c.to_string(),
+            c => format!("\\x{:02x}", c as u8),
+        },
+    }
+}
+
+// Something wrong with a regex, and where it is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegexError {
+    pub(crate) pattern: String,
+    pub(crate) position: usize, // The character where the problem was found, counting from 0.
+    pub(crate) message: String,
+}
+
+// This tells the program how to println a RegexError in a nice way.
+impl std::fmt::Display for RegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}`, at character {}: {}",
+            self.pattern, self.position, self.message
+        )
+    }
+}
+
+// A minimal DFA, with state 0 as the start state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Automaton {
+    pub(crate) transitions: Vec<Vec<Option<usize>>>, // One row of COLUMNS entries for each state. None means that there is no way on.
+    pub(crate) accepts: Vec<Option<usize>>, // The regex that each state accepts, as an index into the list. The earliest regex wins if several match.
+}
+
+impl Automaton {
+    // Generate the minimal DFA that recognizes the given regexes.
+    pub fn generate(patterns: &[&str]) -> Result<Self, RegexError> {
+        let mut nfa = Nfa::default();
+        let start = nfa.add_state();
+        for (index, pattern) in patterns.iter().enumerate() {
+            let regex = RegexParser::new(pattern).parse()?;
+            let (from, to) = nfa.build(&regex);
+            nfa.states[start].epsilon.push(from);
+            nfa.states[to].accepts = Some(index);
+        }
+
+        Ok(nfa.to_dfa(start).minimize())
+    }
+
+    // Run the DFA over the whole of the text, and return the regex that it matches, if any.
+    pub fn matches(&self, text: &str) -> Option<usize> {
+        let mut state = 0;
+        for c in text.chars() {
+            state = self.transitions[state][column(c)]?;
+        }
+
+        self.accepts[state]
+    }
+
+    // Whether there is any way on from the state.
+    pub fn is_dead_end(&self, state: usize) -> bool {
+        self.transitions[state].iter().all(|next| next.is_none())
+    }
+
+    // The shortest text that leads from the start state to each state. This is a handy name for a state.
+    pub fn shortest_paths(&self) -> Vec<String> {
+        let mut paths: Vec<Option<String>> = vec![None; self.transitions.len()];
+        paths[0] = Some(String::new());
+        let mut queue: VecDeque<usize> = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for (column, next) in self.transitions[state].iter().enumerate() {
+                if let Some(next) = next {
+                    if paths[*next].is_none() {
+                        let mut path = paths[state].clone().unwrap();
+                        path.push(representative(column));
+                        paths[*next] = Some(path);
+                        queue.push_back(*next);
+                    }
+                }
+            }
+        }
+
+        paths
+            .into_iter()
+            .map(|path| path.unwrap_or_default())
+            .collect()
+    }
+
+    // Merge the states that behave the same, by refining a partition of the states until each block is consistent.
+    // To begin with, states are only told apart by what they accept. Then a block is split whenever two of its states move to different blocks on the same column.
+    fn minimize(&self) -> Self {
+        let mut block: Vec<usize> = renumber(self.accepts.iter().cloned());
+        loop {
+            let signatures = (0..self.transitions.len()).map(|state| {
+                let moves: Vec<Option<usize>> = self.transitions[state]
+                    .iter()
+                    .map(|next| next.map(|next| block[next]))
+                    .collect();
+                (block[state], moves)
+            });
+            let refined = renumber(signatures);
+            let done = refined.iter().max() == block.iter().max();
+            block = refined;
+            if done {
+                break;
+            }
+        }
+
+        // Number the blocks in the order that they are reached from the start state, so that the start state stays state 0.
+        let mut number: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut order: Vec<usize> = Vec::new();
+        let mut queue: VecDeque<usize> = VecDeque::from([0]);
+        number.insert(block[0], 0);
+        order.push(0);
+        while let Some(state) = queue.pop_front() {
+            for next in self.transitions[state].iter().flatten() {
+                if let Entry::Vacant(entry) = number.entry(block[*next]) {
+                    entry.insert(order.len());
+                    order.push(*next);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        Automaton {
+            transitions: order
+                .iter()
+                .map(|state| {
+                    self.transitions[*state]
+                        .iter()
+                        .map(|next| next.map(|next| number[&block[next]]))
+                        .collect()
+                })
+                .collect(),
+            accepts: order.iter().map(|state| self.accepts[*state]).collect(),
+        }
+    }
+}
+
+// Give each distinct value a number, in the order that they first appear.
+fn renumber<T: Ord>(values: impl Iterator<Item = T>) -> Vec<usize> {
+    let mut numbers: BTreeMap<T, usize> = BTreeMap::new();
+    values
+        .map(|value| {
+            let next = numbers.len();
+            *numbers.entry(value).or_insert(next)
+        })
+        .collect()
+}
+
+// A regex, once it has been parsed.
+#[derive(Clone, Debug, PartialEq)]
+enum Regex {
+    Empty,
+    Class(BTreeSet<usize>), // Any one character from the columns.
+    Concat(Box<Regex>, Box<Regex>),
+    Alternate(Box<Regex>, Box<Regex>),
+    Star(Box<Regex>),
+}
+
+// A recursive descent parser for regexes.
+struct RegexParser {
+    pattern: String,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl RegexParser {
+    fn new(pattern: &str) -> Self {
+        RegexParser {
+            pattern: pattern.to_string(),
+            chars: pattern.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Regex, RegexError> {
+        let regex = self.alternation()?;
+        match self.peek() {
+            None => Ok(regex),
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+        }
+    }
+
+    // alternation ::= concatenation { "|" concatenation }
+    fn alternation(&mut self) -> Result<Regex, RegexError> {
+        let mut regex = self.concatenation()?;
+        while self.peek() == Some('|') {
+            self.position += 1;
+            regex = Regex::Alternate(Box::new(regex), Box::new(self.concatenation()?));
+        }
+
+        Ok(regex)
+    }
+
+    // concatenation ::= { repetition }
+    fn concatenation(&mut self) -> Result<Regex, RegexError> {
+        let mut regex = Regex::Empty;
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let next = self.repetition()?;
+            regex = match regex {
+                Regex::Empty => next,
+                regex => Regex::Concat(Box::new(regex), Box::new(next)),
+            };
+        }
+
+        Ok(regex)
+    }
+
+    // repetition ::= atom { "*" | "+" | "?" }
+    fn repetition(&mut self) -> Result<Regex, RegexError> {
+        let mut regex = self.atom()?;
+        while let Some(c) = self.peek() {
+            regex = match c {
+                '*' => Regex::Star(Box::new(regex)),
+                '+' => Regex::Concat(
+                    Box::new(regex.clone()),
+                    Box::new(Regex::Star(Box::new(regex))),
+                ),
+                '?' => Regex::Alternate(Box::new(regex), Box::new(Regex::Empty)),
+                _ => break,
+            };
+            self.position += 1;
+        }
+
+        Ok(regex)
+    }
+
+    // atom ::= "(" alternation ")" | "[" class "]" | "." | "\" character | character
+    fn atom(&mut self) -> Result<Regex, RegexError> {
+        let c = match self.next() {
+            Some(c) => c,
+            None => return Err(self.error("unexpected end of the regex")),
+        };
+
+        match c {
+            '(' => {
+                let regex = self.alternation()?;
+                if self.next() != Some(')') {
+                    return Err(self.error("expected `)`"));
+                }
+                Ok(regex)
+            }
+            '[' => Ok(Regex::Class(self.class()?)),
+            '.' => Ok(Regex::Class((0..COLUMNS).collect())),
+            '\\' => Ok(Regex::Class(self.escape()?)),
+            '*' | '+' | '?' => Err(self.error(&format!("nothing to repeat before `{}`", c))),
+            c => Ok(Regex::Class(BTreeSet::from([column(c)]))),
+        }
+    }
+
+    // class ::= [ "^" ] { character [ "-" character ] | "\" character } "]"
+    fn class(&mut self) -> Result<BTreeSet<usize>, RegexError> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+
+        let mut columns = BTreeSet::new();
+        loop {
+            let c = match self.next() {
+                Some(']') if !columns.is_empty() => break,
+                Some('\\') => {
+                    columns.extend(self.escape()?);
+                    continue;
+                }
+                Some(c) => c,
+                None => return Err(self.error("expected `]`")),
+            };
+
+            if self.peek() == Some('-') && self.chars.get(self.position + 1) != Some(&']') {
+                self.position += 1;
+                let last = match self.next() {
+                    Some(last) if c.is_ascii() && last.is_ascii() && c <= last => last,
+                    _ => return Err(self.error("a range must be of ASCII characters, in order")),
+                };
+                columns.extend((c as usize)..=(last as usize));
+            } else {
+                columns.insert(column(c));
+            }
+        }
+
+        if negated {
+            columns = (0..COLUMNS).filter(|c| !columns.contains(c)).collect();
+        }
+
+        Ok(columns)
+    }
+
+    // The columns that an escaped character stands for.
+    fn escape(&mut self) -> Result<BTreeSet<usize>, RegexError> {
+        let all = (0..COLUMNS).map(representative);
+        let columns = match self.next() {
+            Some('s') => all.filter(|c| is_separator(*c)).map(column).collect(),
+            Some('S') => all.filter(|c| !is_separator(*c)).map(column).collect(),
+            Some('d') => all.filter(|c| c.is_ascii_digit()).map(column).collect(),
+            Some('a') => all.filter(|c| c.is_alphabetic()).map(column).collect(),
+            Some(c) => BTreeSet::from([column(c)]),
+            None => return Err(self.error("unexpected end of the regex")),
+        };
+
+        Ok(columns)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+
+        c
+    }
+
+    fn error(&self, message: &str) -> RegexError {
+        RegexError {
+            pattern: self.pattern.clone(),
+            position: self.position.min(self.chars.len()),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct NfaState {
+    epsilon: Vec<usize>,
+    edge: Option<(BTreeSet<usize>, usize)>, // At most one labelled edge, on any of a set of columns.
+    accepts: Option<usize>,
+}
+
+#[derive(Default)]
+struct Nfa {
+    states: Vec<NfaState>,
+}
+
+impl Nfa {
+    fn add_state(&mut self) -> usize {
+        self.states.push(NfaState::default());
+        self.states.len() - 1
+    }
+
+    // Thompson's construction: build the fragment for a regex, and return its entry and exit states.
+    fn build(&mut self, regex: &Regex) -> (usize, usize) {
+        let from = self.add_state();
+        let to = self.add_state();
+
+        match regex {
+            Regex::Empty => self.states[from].epsilon.push(to),
+            Regex::Class(columns) => self.states[from].edge = Some((columns.clone(), to)),
+            Regex::Concat(first, second) => {
+                let (first_from, first_to) = self.build(first);
+                let (second_from, second_to) = self.build(second);
+                self.states[from].epsilon.push(first_from);
+                self.states[first_to].epsilon.push(second_from);
+                self.states[second_to].epsilon.push(to);
+            }
+            Regex::Alternate(first, second) => {
+                for regex in [first, second] {
+                    let (inner_from, inner_to) = self.build(regex);
+                    self.states[from].epsilon.push(inner_from);
+                    self.states[inner_to].epsilon.push(to);
+                }
+            }
+            Regex::Star(inner) => {
+                let (inner_from, inner_to) = self.build(inner);
+                self.states[from].epsilon.extend([inner_from, to]);
+                self.states[inner_to].epsilon.extend([inner_from, to]);
+            }
+        }
+
+        (from, to)
+    }
+
+    // Every state that can be reached from the given ones without reading a character.
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                stack.extend(self.states[state].epsilon.iter().copied());
+            }
+        }
+
+        closure
+    }
+
+    // The subset construction: each state of the DFA is the set of NFA states that the NFA could be in.
+    fn to_dfa(&self, start: usize) -> Automaton {
+        let mut sets: Vec<BTreeSet<usize>> = vec![self.closure([start])];
+        let mut numbers: BTreeMap<BTreeSet<usize>, usize> = BTreeMap::from([(sets[0].clone(), 0)]);
+        let mut transitions: Vec<Vec<Option<usize>>> = Vec::new();
+
+        let mut i = 0;
+        while i < sets.len() {
+            let mut row = Vec::with_capacity(COLUMNS);
+            for column in 0..COLUMNS {
+                let moved = sets[i]
+                    .iter()
+                    .filter_map(|state| match &self.states[*state].edge {
+                        Some((columns, to)) if columns.contains(&column) => Some(*to),
+                        _ => None,
+                    });
+                let next = self.closure(moved);
+                if next.is_empty() {
+                    row.push(None);
+                    continue;
+                }
+                let number = match numbers.get(&next) {
+                    Some(number) => *number,
+                    None => {
+                        sets.push(next.clone());
+                        numbers.insert(next, sets.len() - 1);
+                        sets.len() - 1
+                    }
+                };
+                row.push(Some(number));
+            }
+            transitions.push(row);
+            i += 1;
+        }
+
+        let accepts = sets
+            .iter()
+            .map(|set| {
+                set.iter()
+                    .filter_map(|state| self.states[*state].accepts)
+                    .min()
+            })
+            .collect();
+
+        Automaton {
+            transitions,
+            accepts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod lexgen_tests {
+    use crate::lexgen::*;
+
+    #[test]
+    fn test_literals_and_priority() {
+        let automaton = Automaton::generate(&["if", "[a-z]+"]).unwrap();
+
+        assert_eq!(automaton.matches("if"), Some(0));
+        assert_eq!(automaton.matches("i"), Some(1));
+        assert_eq!(automaton.matches("iff"), Some(1));
+        assert_eq!(automaton.matches("IF"), None);
+        assert_eq!(automaton.matches(""), None);
+    }
+
+    #[test]
+    fn test_operators() {
+        let automaton = Automaton::generate(&["a(b|c)*d?", "x+"]).unwrap();
+
+        for text in ["a", "abcbc", "ad", "acd"] {
+            assert_eq!(automaton.matches(text), Some(0), "{}", text);
+        }
+        assert_eq!(automaton.matches("add"), None);
+        assert_eq!(automaton.matches("xxx"), Some(1));
+    }
+
+    #[test]
+    fn test_classes_and_escapes() {
+        let automaton = Automaton::generate(&["[^a-c]", "\\d\\.\\d", "\\a\\S*\\s", "."]).unwrap();
+
+        assert_eq!(automaton.matches("z"), Some(0));
+        assert_eq!(automaton.matches("b"), Some(3));
+        assert_eq!(automaton.matches("1.5"), Some(1));
+        assert_eq!(automaton.matches("é?;"), Some(2));
+        assert_eq!(automaton.matches("é? "), Some(2));
+    }
+
+    #[test]
+    fn test_minimization() {
+        // (a|b)*abb has the well-known minimal DFA with four states.
+        let automaton = Automaton::generate(&["(a|b)*abb"]).unwrap();
+
+        assert_eq!(automaton.transitions.len(), 4);
+        assert_eq!(automaton.matches("babaabb"), Some(0));
+        assert_eq!(automaton.matches("abba"), None);
+
+        // The same language written two ways gives the same DFA.
+        assert_eq!(
+            Automaton::generate(&["a+"]).unwrap(),
+            Automaton::generate(&["aa*|a"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let automaton = Automaton::generate(&["ab", "c"]).unwrap();
+
+        let mut paths = automaton.shortest_paths();
+        paths.sort();
+        assert_eq!(paths, vec!["", "a", "ab", "c"]);
+        // The states are numbered in the order that they are reached: `a`, then `c`, then `ab`.
+        assert!(!automaton.is_dead_end(1));
+        assert!(automaton.is_dead_end(2));
+        assert!(automaton.is_dead_end(3));
+    }
+
+    #[test]
+    fn test_describe_columns() {
+        let columns = |text: &str| text.chars().map(column).collect::<BTreeSet<usize>>();
+
+        assert_eq!(describe_columns(&columns("a")), "a");
+        assert_eq!(describe_columns(&columns("abcdxz-")), "[\\-a-dxz]");
+        assert_eq!(describe_columns(&columns("\n\t ")), "[\\t\\n\\x20]");
+        assert_eq!(
+            describe_columns(&columns("é€")),
+            "[<non-ASCII letter><non-ASCII symbol>]"
+        );
+        let all_but: BTreeSet<usize> = (0..COLUMNS).filter(|c| *c != column(';')).collect();
+        assert_eq!(describe_columns(&all_but), "[^;]");
+        assert_eq!(describe_columns(&(0..COLUMNS).collect()), "any");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = Automaton::generate(&["a(b"]).unwrap_err();
+        assert_eq!(error.to_string(), "`a(b`, at character 3: expected `)`");
+
+        assert!(Automaton::generate(&["*a"]).is_err());
+        assert!(Automaton::generate(&["[a-"]).is_err());
+        assert!(Automaton::generate(&["[z-a]"]).is_err());
+        assert!(Automaton::generate(&["a)"]).is_err());
+    }
+}
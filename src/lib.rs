@@ -0,0 +1,248 @@
+#![warn(clippy::all)]
+
+// The syntax analyzer for Simple Scala, as a library. The command-line program in main.rs is built on top of it, and so can any other tool.
+// The simplest way in is through `scan` and `parse` below. For more control, such as parsing with a grammar from a file, use `Parser` directly.
+
+// lazy_static is a third-party library that enables the creation of "static" (create once, use forever) data structures in Rust.
+#[macro_use]
+extern crate lazy_static;
+
+pub mod ast;
+pub mod bnf;
+pub mod bookkeeper;
+mod cursor;
+pub mod derivation;
+pub mod dfa;
+pub mod diagnostic;
+pub mod dot;
+pub mod error;
+pub mod grammar;
+pub mod lexgen;
+pub mod log;
+pub mod parser;
+mod pda;
+pub mod pool;
+pub mod rules;
+pub mod scanner;
+pub mod semantic;
+mod stack;
+pub mod symbol;
+pub mod trace;
+pub mod tree;
+
+pub use crate::ast::Program;
+pub use crate::bookkeeper::{
+    Bookkeeper, CrossReference, Declaration, Occurrence, Role, SymbolKind, SymbolTableToken,
+    SymbolType, Token,
+};
+pub use crate::derivation::Derivation;
+pub use crate::diagnostic::{Diagnostic, Span};
+pub use crate::error::{Error, ErrorType};
+pub use crate::grammar::{Grammar, GrammarReport};
+pub use crate::parser::Parser;
+pub use crate::pda::ParsingTables;
+pub use crate::scanner::Scanner;
+pub use crate::symbol::{NonTerminal, Symbol, Terminal};
+pub use crate::trace::{SilentTrace, Step, TableTrace, Trace};
+pub use crate::tree::ParseTree;
+
+// The tokens of a source program, along with any lexical errors and the symbol table that was filled in along the way.
+#[derive(Clone, Debug)]
+pub struct ScanResult {
+    pub(crate) tokens: Vec<Token>,
+    pub(crate) errors: Vec<Error>,
+    pub(crate) symtab: Bookkeeper,
+}
+
+impl ScanResult {
+    // Every token in the source, in order. Tokens with lexical errors are left out.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    // Every lexical error in the source, in order.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn symtab(&self) -> &Bookkeeper {
+        &self.symtab
+    }
+}
+
+// The outcome of parsing a source program.
+#[derive(Clone, Debug)]
+pub struct ParseResult {
+    pub(crate) accepted: bool,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) symtab: Bookkeeper,
+    pub(crate) program: Option<Program>,
+    pub(crate) semantic: Vec<Diagnostic>,
+    pub(crate) tree: ParseTree,
+}
+
+impl ParseResult {
+    // Whether the source is a valid program. This is only true if there are no diagnostics.
+    pub fn accepted(&self) -> bool {
+        self.accepted
+    }
+
+    // Every lexical and syntax error found in the source, in order.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn symtab(&self) -> &Bookkeeper {
+        &self.symtab
+    }
+
+    // The abstract syntax tree, if the source was accepted by the built-in grammar.
+    pub fn program(&self) -> Option<&Program> {
+        self.program.as_ref()
+    }
+
+    // The errors and warnings that the semantic analysis found in the abstract syntax tree, such as identifiers that were never declared.
+    // They have no say in whether the source was accepted, since that is a matter of its syntax. Empty if there is no tree.
+    pub fn semantic_diagnostics(&self) -> &[Diagnostic] {
+        &self.semantic
+    }
+
+    // The concrete parse tree. It is there even if the source was rejected, with a gap wherever an error was.
+    pub fn tree(&self) -> &ParseTree {
+        &self.tree
+    }
+}
+
+// Scan a source program into tokens.
+pub fn scan(source: &str) -> ScanResult {
+    let mut scanner = Scanner::new(source.to_string(), Bookkeeper::new());
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut errors: Vec<Error> = Vec::new();
+
+    loop {
+        match scanner.token_request() {
+            (Some(token), _, _) => tokens.push(token.clone()),
+            (None, Some(error), _) => errors.push(error.clone()),
+            (None, None, _) => break,
+        }
+    }
+
+    ScanResult {
+        tokens,
+        errors,
+        symtab: scanner.symtab,
+    }
+}
+
+// Parse a source program with the built-in grammar of Simple Scala.
+pub fn parse(source: &str) -> ParseResult {
+    finish(Parser::new(source.to_string(), Bookkeeper::new()))
+}
+
+// Parse a source program with another grammar, such as one loaded with `bnf::parse`.
+// A grammar that is not LL(1) cannot be parsed with, so what is wrong with it is returned instead.
+pub fn parse_with_grammar(
+    source: &str,
+    grammar: &Grammar,
+) -> Result<ParseResult, Box<GrammarReport>> {
+    Parser::with_grammar(source.to_string(), Bookkeeper::new(), grammar).map(finish)
+}
+
+// Run the parser to the end, and collect what it found.
+fn finish(mut parser: Parser) -> ParseResult {
+    let accepted = parser.parse_with(&mut SilentTrace);
+    let program = parser.program();
+    let semantic = program.as_ref().map_or_else(Vec::new, |program| {
+        semantic::analyze(program, &parser.file, &mut parser.scanner.symtab)
+    });
+    let tree = parser.tree().clone();
+
+    ParseResult {
+        accepted,
+        diagnostics: parser.errors,
+        symtab: parser.scanner.symtab,
+        program,
+        semantic,
+        tree,
+    }
+}
+
+#[cfg(test)]
+mod lib_tests {
+    use crate::*;
+
+    #[test]
+    fn test_scan() {
+        let result = scan("package a;\n");
+
+        let tokens: Vec<&str> = result.tokens().iter().map(|t| t.token()).collect();
+        assert_eq!(tokens, vec!["package", "a", ";"]);
+        assert_eq!(result.tokens()[1].terminal(), Terminal::Id);
+        assert!(result.errors().is_empty());
+        assert_eq!(result.symtab().symbols().count(), 1);
+    }
+
+    #[test]
+    fn test_scan_keeps_going_after_an_error() {
+        let result = scan("package 1a;\nimport b;\n");
+
+        assert_eq!(result.errors().len(), 1);
+        assert_eq!(
+            result.errors()[0].error_type(),
+            &ErrorType::IdentifierBeginsWithNumber
+        );
+        assert_eq!(result.tokens().last().unwrap().line_number(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        let result = parse("package a;\nabstract class {\n}\n$\n");
+
+        assert!(result.accepted());
+        assert!(result.diagnostics().is_empty());
+        let program = result.program().unwrap();
+        assert_eq!(program.packages[0].name, "a");
+        assert_eq!(program.bodies.len(), 1);
+        assert_eq!(result.tree().roots().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_with_errors() {
+        let result = parse("package a\nimport b;\n$\n");
+
+        assert!(!result.accepted());
+        assert!(result.program().is_none());
+        assert_eq!(result.diagnostics()[0].line_number(), 2);
+        assert_eq!(result.diagnostics()[0].expected(), &[Terminal::Semicolon]);
+    }
+
+    #[test]
+    fn test_semantic_diagnostics() {
+        let result = parse("abstract class {\nval a : int;\na <= b;\n}\n$\n");
+
+        assert!(result.accepted());
+        assert_eq!(result.semantic_diagnostics().len(), 1);
+        assert_eq!(
+            result.semantic_diagnostics()[0].to_string(),
+            "<source>:3:6: Undeclared identifier `b`"
+        );
+        assert!(parse("package a\n$\n").semantic_diagnostics().is_empty());
+
+        let declared = result.symtab().lookup(1, "a").unwrap();
+        assert_eq!(declared.kind(), SymbolKind::Variable);
+        assert_eq!(
+            result.symtab().scope_path(declared.scope()),
+            "program::class 1"
+        );
+    }
+
+    #[test]
+    fn test_parse_with_grammar() {
+        let grammar = bnf::parse(include_str!("../grammars/simple_scala.bnf")).unwrap();
+        let result = parse_with_grammar("package a;\n$\n", &grammar).unwrap();
+
+        assert!(result.accepted());
+        // The file has the same rules as the built-in grammar, so the tree can still be built.
+        assert!(result.program().is_some());
+    }
+}
@@ -0,0 +1,195 @@
+#![warn(clippy::all)]
+
+// Leveled logging of what the scanner, the stack and the PDA are doing, written to stderr. This is for debugging the parser itself; the trace is what shows a user the steps of the parsing.
+// Nothing is logged unless it is turned on, either with the PARSER_LOG environment variable or with `set_filter` (which is what the command line's `--log` does).
+// A filter is a level, such as `debug`, or a comma-separated list of levels for each part of the parser, such as `pda=debug,scanner=trace`. The parts are the modules that log: scanner, stack, pda, parser and semantic.
+
+use std::env;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+// The environment variable that the filter is read from when the program starts.
+pub const ENV_VAR: &str = "PARSER_LOG";
+
+// How much detail a message goes into. Each level also shows everything at the levels before it.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug, // Decisions, such as the rule that the PDA used or the state that the DFA went to.
+    Trace, // Everything, such as each character read and each push and pop.
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Level> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+// This tells the program how to println a Level in a nice way.
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let printable = match *self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+
+        write!(f, "{}", printable)
+    }
+}
+
+// Which messages to log. A level of None means that nothing is logged.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Filter {
+    pub(crate) level: Option<Level>, // For any part that is not listed in `targets`.
+    pub(crate) targets: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    // Read a filter such as `debug`, `off` or `pda=debug,scanner=trace`.
+    pub fn parse(spec: &str) -> Result<Filter, String> {
+        let level = |name: &str| -> Result<Option<Level>, String> {
+            match name.trim() {
+                "off" => Ok(None),
+                name => Level::from_name(name)
+                    .map(Some)
+                    .ok_or_else(|| format!("unknown log level `{}`", name)),
+            }
+        };
+
+        let mut filter = Filter::default();
+        for part in spec.split(',').filter(|part| !part.trim().is_empty()) {
+            match part.split_once('=') {
+                Some((target, name)) => filter
+                    .targets
+                    .push((target.trim().to_string(), level(name)?)),
+                None => filter.level = level(part)?,
+            }
+        }
+
+        Ok(filter)
+    }
+
+    // Whether a message from the given part of the parser is logged.
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let allowed = self
+            .targets
+            .iter()
+            .rev()
+            .find(|(t, _)| t == target)
+            .map_or(self.level, |(_, allowed)| *allowed);
+
+        allowed.is_some_and(|allowed| level <= allowed)
+    }
+
+    // The most detailed level that anything is logged at, as a number, with 0 for nothing.
+    fn max_level(&self) -> u8 {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .chain([self.level])
+            .map(|level| level.map_or(0, |level| level as u8))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+lazy_static! {
+    // The filter starts out as whatever is in the environment variable.
+    static ref FILTER: RwLock<Filter> = RwLock::new(match env::var(ENV_VAR) {
+        Ok(spec) => Filter::parse(&spec).unwrap_or_else(|error| {
+            eprintln!("Ignoring {}: {}", ENV_VAR, error);
+            Filter::default()
+        }),
+        Err(_) => Filter::default(),
+    });
+    // Kept alongside the filter, so that most messages can be turned away without taking the lock.
+    static ref MAX_LEVEL: AtomicU8 = AtomicU8::new(FILTER.read().unwrap().max_level());
+}
+
+// Replace the filter, such as the one from the environment variable.
+pub fn set_filter(filter: Filter) {
+    // The maximum level has to be stored before the lock is taken, since it reads the filter the first time that it is used.
+    MAX_LEVEL.store(filter.max_level(), Ordering::Relaxed);
+    *FILTER.write().unwrap() = filter;
+}
+
+// Whether a message from the given part of the parser is logged.
+pub fn enabled(level: Level, target: &str) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+        && FILTER.read().unwrap().enabled(level, target)
+}
+
+// Write a message to stderr, such as `[DEBUG pda] <scala> on package: rule 1`.
+pub fn write(level: Level, target: &str, message: std::fmt::Arguments) {
+    eprintln!("[{} {}] {}", level, target, message);
+}
+
+// The part of the parser that a module path such as `parser::scanner` is for.
+pub(crate) fn target(module_path: &'static str) -> &'static str {
+    module_path.rsplit("::").next().unwrap_or(module_path)
+}
+
+// Log a message from the module that this is used in. The message is only formatted if it is going to be logged.
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let target = $crate::log::target(module_path!());
+        if $crate::log::enabled($level, target) {
+            $crate::log::write($level, target, format_args!($($arg)+));
+        }
+    }};
+}
+pub(crate) use log;
+
+#[cfg(test)]
+mod log_tests {
+    use crate::log::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Filter::parse("").unwrap(), Filter::default());
+        assert_eq!(Filter::parse("DEBUG").unwrap().level, Some(Level::Debug));
+        assert_eq!(
+            Filter::parse("info, pda=trace,scanner=off").unwrap(),
+            Filter {
+                level: Some(Level::Info),
+                targets: vec![
+                    ("pda".to_string(), Some(Level::Trace)),
+                    ("scanner".to_string(), None)
+                ],
+            }
+        );
+        assert!(Filter::parse("loud").is_err());
+        assert!(Filter::parse("pda=loud").is_err());
+    }
+
+    #[test]
+    fn test_enabled() {
+        let filter = Filter::parse("warn,pda=debug,scanner=off").unwrap();
+
+        assert!(filter.enabled(Level::Error, "stack"));
+        assert!(!filter.enabled(Level::Info, "stack"));
+        assert!(filter.enabled(Level::Debug, "pda"));
+        assert!(!filter.enabled(Level::Trace, "pda"));
+        assert!(!filter.enabled(Level::Error, "scanner"));
+        assert_eq!(filter.max_level(), Level::Debug as u8);
+        assert_eq!(Filter::default().max_level(), 0);
+    }
+
+    #[test]
+    fn test_target() {
+        assert_eq!(target("parser::scanner"), "scanner");
+        assert_eq!(target("parser"), "parser");
+    }
+}
@@ -2,73 +2,1068 @@
 
 // Importing standard library modules that we need.
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
+use std::io;
 use std::io::prelude::*;
+use std::io::BufWriter;
 use std::path::Path;
 use std::process;
+use std::sync::Arc;
 
 // This is a third-party library that enables printing of styled text to the terminal. It is not noticeable in the .txt output, but it was helpful in debugging this program.
 use colored::*;
-// lazy_static is a third-party library that enables the creation of "static" (create once, use forever) data structures in Rust.
-#[macro_use]
-extern crate lazy_static;
+// Everything but the command line itself lives in the library, in lib.rs.
+use parser::bookkeeper::SymbolOrder;
+use parser::log::{self, Filter};
+use parser::trace::{CsvTrace, JsonLinesTrace};
+use parser::{bnf, dot, pool, scan, semantic};
+use parser::{
+    Bookkeeper, Diagnostic, ErrorType, Grammar, GrammarReport, Parser, ParsingTables, SilentTrace,
+    TableTrace, Trace,
+};
 
-// Importing our third-party files.
-mod bookkeeper;
-mod error;
-mod parser;
-mod pda;
-mod rules;
-mod scanner;
-mod stack;
+// The exit codes. A source program with lexical or syntax errors, or a grammar that fails its check, is not a problem with how the program was run, so it gets its own code.
+const EXIT_OK: i32 = 0;
+const EXIT_ERRORS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
 
-use crate::bookkeeper::Bookkeeper;
-use crate::parser::Parser;
+const USAGE: &str = "\
+Usage: parser [options] <command> [arguments]
+       parser [options] <filename>            (the same as `parser --verbose parse <filename>`)
 
-pub const DEBUG: bool = false;
+Commands:
+    scan <path>...                 Print the tokens of a source program
+    parse <path>...                Parse a source program and print the steps of the parsing
+    analyze <path>...              Parse a source program and check its declarations and types
+    tree <filename>                Print the parse tree of a source program
+    derivation <filename>          Print the leftmost derivation of an accepted source program
+    symbols <filename>             Print the symbol table of a source program
+    xref <filename>                Print where each identifier and constant of a source program is defined and used
+    table                          Print the LL(1) parsing table of the grammar
+    grammar                        Print the grammar in BNF
+    check [<grammar file>]         Check the grammar for LL(1) conflicts and other problems
+    dot dfa|pda                    Print the scanner's DFA or the parser's PDA as a Graphviz DOT graph
+
+A filename or path of `-` is standard input. `scan`, `parse` and `analyze` also take several paths, or directories to search for
+*.ssc files, and then print ACCEPT or REJECT for each file and a summary at the end.
+
+Options:
+    -g, --grammar <grammar file>   Use a grammar file instead of the built-in grammar
+    -q, --quiet                    Print nothing but errors
+    -v, --verbose                  Print the source program, the errors and the symbol table as well
+        --color auto|always|never  Whether to color the output (default: auto)
+    -o, --output <file>            Write the output to a file instead of stdout
+        --trace table|jsonl|csv|silent
+                                   How to print the steps of the parsing (default: table)
+        --format text|json|dot|csv How to print the parse tree (text, json or dot) or the symbol table (text, json or csv)
+                                   (default: text)
+        --sort seen|name|kind|line The order of the symbol table: as first seen, by name, by kind or by first line
+                                   (default: seen)
+        --log <filter>             Log what the parser is doing to stderr, such as `debug` or `pda=debug,scanner=trace`
+                                   (default: the PARSER_LOG environment variable, or off)
+    -j, --jobs <n>                 How many files of a batch to check at once (default: one for each processor)
+    -h, --help                     Print this message
+
+Exit codes: 0 if everything went well, 1 if the source program has errors or the grammar fails its check, and 2 for anything else.";
+
+// Why a command could not be carried out. It is never the fault of the source program, so it exits with EXIT_USAGE.
+#[derive(Debug)]
+enum Failure {
+    Output(io::Error), // The output could not be written.
+    Message(String), // Anything else, such as a file that could not be read, with what to tell the user.
+}
+
+impl From<io::Error> for Failure {
+    fn from(why: io::Error) -> Self {
+        Failure::Output(why)
+    }
+}
+
+// What the program was asked to do.
+#[derive(Clone, Debug, PartialEq)]
+enum Command {
+    Scan(Vec<String>),
+    Parse(Vec<String>),
+    Analyze(Vec<String>),
+    Tree(String),
+    Derivation(String),
+    Symbols(String),
+    Xref(String),
+    Table,
+    Grammar,
+    Check(Option<String>),
+    Dot(String),
+    Help,
+}
+
+// How much to print.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+// The command, along with the options that were given for it. The options can come before or after the command.
+#[derive(Clone, Debug, PartialEq)]
+struct Options {
+    command: Command,
+    grammar: Option<String>,
+    verbosity: Verbosity,
+    color: String,
+    output: Option<String>,
+    trace: String,
+    format: String,
+    sort: SymbolOrder,
+    log: Option<Filter>,
+    jobs: Option<usize>,
+}
 
 // Main. What gets called when we invoke the program.
 fn main() {
-    // Collect the command-line arguments
-    let args: Vec<String> = env::args().collect();
-    // Check for invalid use and terminate if required
-    if args.len() != 2 {
-        print!("{}", "Usage: ".bold().red());
-        println!("{}", "./scanner <filename>".red());
-        process::exit(1);
-    }
-
-    // Attempt to open the file
-    let path = Path::new(&args[1]);
-    let display = path.display();
-
-    let mut file = match File::open(&path) {
-        Err(why) => panic!("Couldn't open {}: {}", display, why),
-        Ok(file) => file,
+    // Collect the command-line arguments, leaving out the name of the program.
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{} {}\n\n{}", "Error:".bold().red(), message, USAGE);
+            process::exit(EXIT_USAGE);
+        }
     };
 
-    let mut s = String::new();
-    match file.read_to_string(&mut s) {
-        Err(why) => panic!("Couldn't read {}: {}", display, why),
-        Ok(_) => println!("{}\n{}", "Source program:".blue().bold(), s),
+    // A filter given on the command line wins over the environment variable.
+    if let Some(filter) = &options.log {
+        log::set_filter(filter.clone());
+    }
+
+    match options.color.as_str() {
+        "always" => control::set_override(true),
+        "never" => control::set_override(false),
+        // Colors are only any good on a terminal, which colored checks for itself.
+        _ if options.output.is_some() => control::set_override(false),
+        _ => {}
+    }
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(why) => fail(&format!("Couldn't create {}: {}", path, why)),
+        },
+        None => Box::new(io::stdout()),
     };
 
+    // Whatever was written is flushed even if the command failed part way through.
+    let result = run(&options, &mut out, &mut io::stderr());
+    let flushed = out.flush();
+    let code = match result.and_then(|code| flushed.map(|_| code).map_err(Failure::from)) {
+        Ok(code) => code,
+        // Most likely the output was piped into something that stopped reading, such as `head`.
+        Err(Failure::Output(why)) if why.kind() == io::ErrorKind::BrokenPipe => EXIT_OK,
+        Err(Failure::Output(why)) => fail(&format!("Couldn't write the output: {}", why)),
+        Err(Failure::Message(message)) => fail(&message),
+    };
+    process::exit(code);
+}
+
+// Read the command and options from the command-line arguments.
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut grammar = None;
+    let mut verbosity = None;
+    let mut color = "auto".to_string();
+    let mut output = None;
+    let mut trace = "table".to_string();
+    let mut format = "text".to_string();
+    let mut sort = SymbolOrder::default();
+    let mut log = None;
+    let mut jobs = None;
+    let mut help = false;
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        // The value of an option that takes one, checked against the values that it can have.
+        let mut value = |allowed: &[&str]| -> Result<String, String> {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("{} needs a value", arg))?;
+            if !allowed.is_empty() && !allowed.contains(&value.as_str()) {
+                return Err(format!(
+                    "{} must be one of {}, not `{}`",
+                    arg,
+                    allowed.join(", "),
+                    value
+                ));
+            }
+            Ok(value.clone())
+        };
+
+        match arg.as_str() {
+            "-g" | "--grammar" => grammar = Some(value(&[])?),
+            "-q" | "--quiet" => verbosity = Some(Verbosity::Quiet),
+            "-v" | "--verbose" => verbosity = Some(Verbosity::Verbose),
+            "--color" => color = value(&["auto", "always", "never"])?,
+            "-o" | "--output" => output = Some(value(&[])?),
+            "--trace" => trace = value(&["table", "jsonl", "csv", "silent"])?,
+            "--format" => format = value(&[])?,
+            "--sort" => {
+                let value = value(&["seen", "name", "kind", "line"])?;
+                sort = SymbolOrder::from_name(&value).unwrap_or_default();
+            }
+            "--log" => log = Some(Filter::parse(&value(&[])?)?),
+            "-j" | "--jobs" => {
+                let value = value(&[])?;
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => jobs = Some(n),
+                    _ => return Err(format!("{} must be a number above 0, not `{}`", arg, value)),
+                }
+            }
+            "-h" | "--help" => help = true,
+            // A lone `-` is a path, for standard input.
+            arg if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{}`", arg))
+            }
+            arg => positional.push(arg.to_string()),
+        }
+    }
+
+    // The arguments of a command, which has to have between `min` and `max` of them.
+    let arguments = |min: usize, max: usize| -> Result<&[String], String> {
+        let arguments = &positional[1..];
+        if arguments.len() < min || arguments.len() > max {
+            return Err(format!("wrong number of arguments for `{}`", positional[0]));
+        }
+        Ok(arguments)
+    };
+
+    let mut legacy = false;
+    let command = match positional.first().map(|s| s.as_str()) {
+        _ if help => Command::Help,
+        None => return Err("no command given".to_string()),
+        Some("scan") => Command::Scan(arguments(1, usize::MAX)?.to_vec()),
+        Some("parse") => Command::Parse(arguments(1, usize::MAX)?.to_vec()),
+        Some("analyze") => Command::Analyze(arguments(1, usize::MAX)?.to_vec()),
+        Some("tree") => Command::Tree(arguments(1, 1)?[0].clone()),
+        Some("derivation") => Command::Derivation(arguments(1, 1)?[0].clone()),
+        Some("symbols") => Command::Symbols(arguments(1, 1)?[0].clone()),
+        Some("xref") => Command::Xref(arguments(1, 1)?[0].clone()),
+        Some("table") => {
+            arguments(0, 0)?;
+            Command::Table
+        }
+        Some("grammar") => {
+            arguments(0, 0)?;
+            Command::Grammar
+        }
+        Some("check") => Command::Check(arguments(0, 1)?.first().cloned()),
+        Some("dot") => match arguments(1, 1)?[0].as_str() {
+            "dfa" => Command::Dot("dfa".to_string()),
+            "pda" => Command::Dot("pda".to_string()),
+            other => return Err(format!("cannot draw `{}`; try dfa or pda", other)),
+        },
+        // Just a filename is what the program took before it had commands, so it still prints everything.
+        Some(_) if positional.len() == 1 => {
+            legacy = true;
+            Command::Parse(positional.clone())
+        }
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+    };
+
+    // Only some commands can print in another format than text, and each has its own: a tree cannot be a table, and a table cannot be a graph.
+    let formats: &[&str] = match command {
+        Command::Tree(_) => &["text", "json", "dot"],
+        Command::Symbols(_) => &["text", "json", "csv"],
+        _ => &["text"],
+    };
+    if command != Command::Help && !formats.contains(&format.as_str()) {
+        return Err(format!(
+            "--format {} does not work with this command; try {}",
+            format,
+            formats.join(", ")
+        ));
+    }
+
+    let default_verbosity = if legacy {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    };
+
+    Ok(Options {
+        command,
+        grammar,
+        verbosity: verbosity.unwrap_or(default_verbosity),
+        color,
+        output,
+        trace,
+        format,
+        sort,
+        log,
+        jobs,
+    })
+}
+
+// Carry out the command, and return the exit code. Errors in the source program go to `err`.
+fn run(options: &Options, out: &mut dyn Write, err: &mut dyn Write) -> Result<i32, Failure> {
+    let quiet = options.verbosity == Verbosity::Quiet;
+    let grammar = options.grammar.as_deref().map(load_grammar).transpose()?;
+
+    // Dead weight in the grammar does not stop it from being used, but the user should know. A grammar that is not LL(1) is turned down when its tables are built.
+    if let (Some(grammar), false) = (&grammar, quiet) {
+        let report = grammar.check();
+        if !report.is_ok() && report.is_ll1() {
+            writeln!(err, "{}\n{}", "Grammar warnings:".yellow().bold(), report)?;
+        }
+    }
+    // The tables that drive the parser are built once, and shared by every source program.
+    let tables = match &grammar {
+        Some(grammar) => match ParsingTables::new(grammar) {
+            Ok(tables) => Arc::new(tables),
+            Err(report) => return Err(invalid_grammar(&report)),
+        },
+        None => ParsingTables::builtin(),
+    };
+
+    match &options.command {
+        Command::Help => writeln!(out, "{}", USAGE)?,
+        Command::Scan(paths) | Command::Parse(paths) | Command::Analyze(paths) => {
+            // The semantic analysis works on the abstract syntax tree, which only the built-in grammar has.
+            if let (Command::Analyze(_), false) = (&options.command, tables.is_builtin()) {
+                return Err(Failure::Message(
+                    "`analyze` only works with the built-in grammar".to_string(),
+                ));
+            }
+            let (sources, walked) = expand_paths(paths).map_err(Failure::Message)?;
+
+            // One file is checked with all of its output, just like before there were batches.
+            if sources.len() == 1 && !walked {
+                let source = read_file(&sources[0])?;
+                let (accepted, _) = check_source(options, &tables, &sources[0], &source, out, err)?;
+                return Ok(if accepted { EXIT_OK } else { EXIT_ERRORS });
+            }
+
+            // Otherwise, each file gets a line saying whether it was accepted, and there is a summary at the end. With --verbose, each file's usual output comes first.
+            // The files are checked on a pool of threads, but what each one prints is held back until the files before it have been printed, so the output is always in the same order.
+            let mut summary = Summary::default();
+            let jobs = options.jobs.unwrap_or_else(pool::default_jobs);
+            pool::run_in_order(
+                &sources,
+                jobs,
+                |path| check_file(options, &tables, path),
+                |_, report| {
+                    let report = report?;
+                    out.write_all(&report.out)?;
+                    err.write_all(&report.err)?;
+                    match report.checked {
+                        Some((lines, accepted, diagnostics)) => {
+                            summary.add(lines, accepted, &diagnostics)
+                        }
+                        None => summary.unreadable += 1,
+                    }
+                    Ok::<(), Failure>(())
+                },
+            )?;
+            if !quiet {
+                writeln!(out, "\n{}\n{}", "Summary:".blue().bold(), summary)?;
+            }
+
+            if summary.unreadable > 0 {
+                return Ok(EXIT_USAGE);
+            } else if summary.accepted < summary.files {
+                return Ok(EXIT_ERRORS);
+            }
+        }
+        Command::Tree(path) | Command::Derivation(path) => {
+            let source = read_file(path)?;
+            let mut parser = new_parser(path, &source, &tables);
+            let accepted = parser.parse_with(&mut SilentTrace);
+
+            if let Command::Tree(_) = options.command {
+                let tree = parser.tree();
+                match options.format.as_str() {
+                    "json" => writeln!(out, "{}", tree.to_json())?,
+                    "dot" => write!(out, "{}", tree.to_dot())?,
+                    _ => write!(out, "{}", tree.to_text())?,
+                }
+            } else if let Some(derivation) = parser.derivation() {
+                // There is only a derivation if the program was accepted.
+                write!(out, "{}", derivation)?;
+            }
+
+            for error in parser.errors() {
+                writeln!(err, "{}", error.render(&source))?;
+            }
+            if !accepted {
+                return Ok(EXIT_ERRORS);
+            }
+        }
+        Command::Xref(path) => {
+            // Which declaration each identifier stands for is found by the semantic analysis, so it needs the built-in grammar, as `analyze` does.
+            if !tables.is_builtin() {
+                return Err(Failure::Message(
+                    "`xref` only works with the built-in grammar".to_string(),
+                ));
+            }
+            let source = read_file(path)?;
+            let mut parser = new_parser(path, &source, &tables);
+            parser.parse_with(&mut SilentTrace);
+
+            // Without a tree, every identifier is listed by its name alone, as a use.
+            let mut symtab = parser.symtab().clone();
+            let mut diagnostics = parser.errors().to_vec();
+            if let Some(program) = parser.program() {
+                diagnostics.extend(semantic::analyze(&program, parser.file(), &mut symtab));
+            }
+            write!(out, "{}", symtab.cross_references_to_string())?;
+
+            for diagnostic in &diagnostics {
+                writeln!(err, "{}", diagnostic.render(&source))?;
+            }
+            if !diagnostics.iter().all(|d| d.error_type().is_warning()) {
+                return Ok(EXIT_ERRORS);
+            }
+        }
+        Command::Symbols(path) => {
+            let source = read_file(path)?;
+            let result = scan(&source);
+            let symtab = result.symtab();
+            match options.format.as_str() {
+                "json" => writeln!(out, "{}", symtab.symbols_to_json(options.sort))?,
+                "csv" => write!(out, "{}", symtab.symbols_to_csv(options.sort))?,
+                _ => write!(out, "{}", symtab.symbols_to_string(options.sort))?,
+            }
+
+            for error in result.errors() {
+                let diagnostic = Diagnostic::new(&display_name(path), error.clone(), Vec::new());
+                writeln!(err, "{}", diagnostic.render(&source))?;
+            }
+            if !result.errors().is_empty() {
+                return Ok(EXIT_ERRORS);
+            }
+        }
+        Command::Table => {
+            let grammar = grammar.unwrap_or_else(Grammar::builtin);
+            write!(out, "{}", grammar.table_to_string())?;
+        }
+        Command::Grammar => {
+            let grammar = grammar.unwrap_or_else(Grammar::builtin);
+            write!(out, "{}", grammar)?;
+        }
+        Command::Check(path) => {
+            // A grammar file given to the command itself wins over one given with --grammar.
+            let grammar = match path {
+                Some(path) => load_grammar(path)?,
+                None => grammar.unwrap_or_else(Grammar::builtin),
+            };
+            let report = grammar.check();
+            if !quiet {
+                writeln!(out, "{}\n{}", "Grammar check:".blue().bold(), report)?;
+            }
+            if !report.is_ok() {
+                return Ok(EXIT_ERRORS);
+            }
+        }
+        Command::Dot(automaton) => match (automaton.as_str(), &grammar) {
+            ("dfa", _) => write!(out, "{}", dot::dfa())?,
+            (_, Some(grammar)) => match dot::pda_with_grammar(grammar) {
+                Ok(dot) => write!(out, "{}", dot)?,
+                Err(report) => return Err(invalid_grammar(&report)),
+            },
+            (_, None) => write!(out, "{}", dot::pda())?,
+        },
+    }
+
+    Ok(EXIT_OK)
+}
+
+// What checking one file of a batch printed, and what it found: the number of lines, whether it was accepted, and its errors. There is nothing found if the file could not be read.
+struct FileReport {
+    out: Vec<u8>,
+    err: Vec<u8>,
+    checked: Option<(usize, bool, Vec<Diagnostic>)>,
+}
+
+// Check one file of a batch, keeping what it prints to be printed later.
+fn check_file(
+    options: &Options,
+    tables: &Arc<ParsingTables>,
+    path: &str,
+) -> Result<FileReport, Failure> {
+    let quiet = options.verbosity == Verbosity::Quiet;
+    let mut report = FileReport {
+        out: Vec::new(),
+        err: Vec::new(),
+        checked: None,
+    };
+
+    let source = match read_source(path) {
+        Ok(source) => source,
+        Err(why) => {
+            writeln!(report.err, "{} {}", "Error:".bold().red(), why)?;
+            if !quiet {
+                writeln!(report.out, "{} {}", "ERROR ".red().bold(), path)?;
+            }
+            return Ok(report);
+        }
+    };
+
+    // Only --verbose prints each file's usual output, and then it is what would be printed without it.
+    let verbosity = match options.verbosity {
+        Verbosity::Verbose => Verbosity::Normal,
+        _ => Verbosity::Quiet,
+    };
+    if verbosity == Verbosity::Normal {
+        writeln!(report.out, "==> {} <==", path)?;
+    }
+    let file_options = Options {
+        verbosity,
+        ..options.clone()
+    };
+    let (accepted, diagnostics) = check_source(
+        &file_options,
+        tables,
+        path,
+        &source,
+        &mut report.out,
+        &mut report.err,
+    )?;
+
+    if !quiet {
+        if accepted {
+            writeln!(report.out, "{} {}", "ACCEPT".green().bold(), path)?;
+        } else {
+            writeln!(
+                report.out,
+                "{} {} ({})",
+                "REJECT".red().bold(),
+                path,
+                count(
+                    diagnostics
+                        .iter()
+                        .filter(|d| !d.error_type().is_warning())
+                        .count(),
+                    "error"
+                )
+            )?;
+        }
+    }
+    report.checked = Some((source.lines().count(), accepted, diagnostics));
+
+    Ok(report)
+}
+
+// Scan, parse or analyze a source program, as the command says, and print what the verbosity calls for. The errors go to `err`, so that they do not get in the way of the output, unless everything is being printed.
+// Return whether the program was accepted, and its errors.
+fn check_source(
+    options: &Options,
+    tables: &Arc<ParsingTables>,
+    path: &str,
+    source: &str,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> Result<(bool, Vec<Diagnostic>), Failure> {
+    let quiet = options.verbosity == Verbosity::Quiet;
+    let verbose = options.verbosity == Verbosity::Verbose;
+    if verbose {
+        writeln!(out, "{}\n{}", "Source program:".blue().bold(), source)?;
+    }
+
+    if let Command::Scan(_) = options.command {
+        let result = scan(source);
+        if !quiet {
+            if verbose {
+                writeln!(out, "{}\n", "Tokens:".blue().bold())?;
+            }
+            writeln!(
+                out,
+                "{0: <30} | {1: <30} | {2: <30}",
+                "Token", "Symbol Type", "Line"
+            )?;
+            for token in result.tokens() {
+                writeln!(out, "{}", token)?;
+            }
+            if verbose {
+                writeln!(out)?;
+                write_symbol_table(out, result.symtab(), options.sort)?;
+            }
+        }
+
+        // Lexical errors have nothing to expect, so they are rendered without a list of what was expected.
+        let diagnostics: Vec<Diagnostic> = result
+            .errors()
+            .iter()
+            .map(|error| Diagnostic::new(&display_name(path), error.clone(), Vec::new()))
+            .collect();
+        for diagnostic in &diagnostics {
+            writeln!(err, "{}", diagnostic.render(source))?;
+        }
+
+        return Ok((diagnostics.is_empty(), diagnostics));
+    }
+
+    let mut parser = new_parser(path, source, tables);
+    if let Command::Analyze(_) = options.command {
+        return analyze_source(options, parser, source, out, err);
+    }
+    if verbose {
+        writeln!(out, "{}\n", "Parse Output:".blue().bold())?;
+    }
+
+    // How to print the steps of the parsing: as a table (the default), as JSON Lines, as CSV, or not at all.
+    let accepted = {
+        let mut trace: Box<dyn Trace + '_> = match options.trace.as_str() {
+            _ if quiet => Box::new(SilentTrace),
+            "jsonl" => Box::new(JsonLinesTrace::new(&mut *out)),
+            "csv" => Box::new(CsvTrace::new(&mut *out)),
+            "silent" => Box::new(SilentTrace),
+            _ => Box::new(TableTrace::new(&mut *out)),
+        };
+        let accepted = parser.parse_with(trace.as_mut());
+        if let Some(why) = trace.take_error() {
+            return Err(why.into());
+        }
+        accepted
+    };
+
+    if verbose {
+        // Print out every error that we found, if there were any.
+        if !parser.errors().is_empty() {
+            writeln!(out, "\n{}", "Errors:".red().bold())?;
+            for error in parser.errors() {
+                writeln!(out, "{}", error.render(source))?;
+            }
+            writeln!(out)?;
+        }
+        write_symbol_table(out, parser.symtab(), options.sort)?;
+    } else {
+        for error in parser.errors() {
+            writeln!(err, "{}", error.render(source))?;
+        }
+    }
+
+    Ok((accepted, parser.errors().to_vec()))
+}
+
+// Parse a source program without printing the steps, and then check its declarations, if it was accepted. Warnings are printed, but they do not stop it from being accepted.
+fn analyze_source(
+    options: &Options,
+    mut parser: Parser,
+    source: &str,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> Result<(bool, Vec<Diagnostic>), Failure> {
+    let verbose = options.verbosity == Verbosity::Verbose;
+    parser.parse_with(&mut SilentTrace);
+
+    // The analysis fills in the scopes of the symbol table, on top of what the scanner put in it.
+    let mut symtab = parser.symtab().clone();
+    let mut diagnostics = parser.errors().to_vec();
+    if let Some(program) = parser.program() {
+        diagnostics.extend(semantic::analyze(&program, parser.file(), &mut symtab));
+    }
+    let accepted =
+        parser.errors().is_empty() && diagnostics.iter().all(|d| d.error_type().is_warning());
+
+    if verbose {
+        if !diagnostics.is_empty() {
+            writeln!(out, "{}", "Errors:".red().bold())?;
+            for diagnostic in &diagnostics {
+                writeln!(out, "{}", diagnostic.render(source))?;
+            }
+            writeln!(out)?;
+        }
+        write_symbol_table(out, &symtab, options.sort)?;
+        writeln!(
+            out,
+            "\n{}\n{}",
+            "Declarations:".blue().bold(),
+            symtab.scopes_to_string(options.sort)
+        )?;
+    } else {
+        for diagnostic in &diagnostics {
+            writeln!(err, "{}", diagnostic.render(source))?;
+        }
+    }
+
+    Ok((accepted, diagnostics))
+}
+
+// The totals for a batch of source programs.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Summary {
+    files: usize, // Not counting those that could not be read.
+    accepted: usize,
+    unreadable: usize,
+    lexical_errors: usize,
+    syntax_errors: usize,
+    semantic_errors: usize,
+    warnings: usize,
+    lines: usize,
+}
+
+impl Summary {
+    // Count a source program that was checked.
+    fn add(&mut self, lines: usize, accepted: bool, diagnostics: &[Diagnostic]) {
+        self.files += 1;
+        if accepted {
+            self.accepted += 1;
+        }
+        for diagnostic in diagnostics {
+            let error_type = diagnostic.error_type();
+            if error_type.is_warning() {
+                self.warnings += 1;
+            } else if error_type.is_semantic() {
+                self.semantic_errors += 1;
+            } else if *error_type == ErrorType::SyntaxError {
+                self.syntax_errors += 1;
+            } else {
+                self.lexical_errors += 1;
+            }
+        }
+        self.lines += lines;
+    }
+}
+
+// This tells the program how to println a summary in a nice way.
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}, {}: {} accepted, {} rejected",
+            count(self.files, "file"),
+            count(self.lines, "line"),
+            self.accepted,
+            self.files - self.accepted
+        )?;
+        writeln!(
+            f,
+            "{}, {}",
+            count(self.lexical_errors, "lexical error"),
+            count(self.syntax_errors, "syntax error")
+        )?;
+        // Only `analyze` finds these, so there is no need to mention them otherwise.
+        if self.semantic_errors > 0 || self.warnings > 0 {
+            writeln!(
+                f,
+                "{}, {}",
+                count(self.semantic_errors, "semantic error"),
+                count(self.warnings, "warning")
+            )?;
+        }
+        if self.unreadable > 0 {
+            writeln!(f, "{} could not be read", count(self.unreadable, "file"))?;
+        }
+
+        Ok(())
+    }
+}
+
+// A number of things, such as "1 error" or "2 errors".
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+// Make a parser for a source program, driven by the tables of the grammar that it is parsed with.
+fn new_parser(path: &str, source: &str, tables: &Arc<ParsingTables>) -> Parser {
     //Initialize the symbol table
     let symtab: Bookkeeper = Bookkeeper::new();
 
-    let mut parser: Parser = Parser::new(s, symtab);
+    let mut parser = Parser::with_tables(source.to_string(), symtab, tables.clone());
+    parser.set_file(&display_name(path));
 
-    println!("{}\n", "Parse Output:".blue().bold());
-    parser.parse();
+    parser
+}
+
+// Print out the contents of the symbol table, in the order that --sort asks for.
+fn write_symbol_table(
+    out: &mut dyn Write,
+    symtab: &Bookkeeper,
+    order: SymbolOrder,
+) -> io::Result<()> {
+    writeln!(out, "{}", "Symbol table contents:".blue().bold())?;
+    write!(out, "{}", symtab.symbols_to_string(order))
+}
+
+// The source programs that the paths on the command line stand for, in order. A directory stands for every *.ssc file in it and in the directories under it, sorted by path.
+// Also return whether there were any directories, since a directory with only one file in it is still a batch.
+fn expand_paths(paths: &[String]) -> Result<(Vec<String>, bool), String> {
+    let mut sources = Vec::new();
+    let mut walked = false;
+    for path in paths {
+        if path != "-" && Path::new(path).is_dir() {
+            walked = true;
+            walk(Path::new(path), &mut sources)
+                .map_err(|why| format!("Couldn't read the directory {}: {}", path, why))?;
+        } else {
+            sources.push(path.clone());
+        }
+    }
+
+    Ok((sources, walked))
+}
+
+fn walk(directory: &Path, sources: &mut Vec<String>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        // Links to directories are not followed, so that a link back up cannot send us around in circles.
+        if entry.file_type()?.is_dir() {
+            walk(&path, sources)?;
+        } else if path.extension().is_some_and(|extension| extension == "ssc") {
+            sources.push(path.display().to_string());
+        }
+    }
+
+    Ok(())
+}
+
+// The name of a source program in diagnostics. `-` is standard input.
+fn display_name(path: &str) -> String {
+    match path {
+        "-" => "<stdin>".to_string(),
+        path => Path::new(path).display().to_string(),
+    }
+}
+
+// Read the whole of a source program into a string. `-` reads standard input.
+fn read_source(path: &str) -> Result<String, String> {
+    let mut s = String::new();
+    let result = match path {
+        "-" => io::stdin().read_to_string(&mut s),
+        path => File::open(path).and_then(|mut file| file.read_to_string(&mut s)),
+    };
+
+    match result {
+        Ok(_) => Ok(s),
+        Err(why) => Err(format!("Couldn't read {}: {}", display_name(path), why)),
+    }
+}
+
+// Read the whole of a file into a string.
+fn read_file(path: &str) -> Result<String, Failure> {
+    read_source(path).map_err(Failure::Message)
+}
+
+// Load a grammar file. It does not have to be LL(1) yet, since `check` is there to say what is wrong with it.
+fn load_grammar(path: &str) -> Result<Grammar, Failure> {
+    let text = read_file(path)?;
+
+    bnf::parse(&text)
+        .map_err(|error| Failure::Message(format!("Invalid grammar {}: {}", path, error)))
+}
+
+// Say why a grammar cannot be used by the parser.
+fn invalid_grammar(report: &GrammarReport) -> Failure {
+    Failure::Message(format!("Invalid grammar: it is not LL(1)\n{}", report))
+}
+
+// Print an error that has nothing to do with the source program, and terminate. Only `main` does this, so that `run` can be tested.
+fn fail(message: &str) -> ! {
+    eprintln!("{} {}", "Error:".bold().red(), message);
+    process::exit(EXIT_USAGE);
+}
+
+#[cfg(test)]
+mod main_tests {
+    use crate::*;
+
+    fn args(line: &str) -> Result<Options, String> {
+        let args: Vec<String> = line.split_whitespace().map(|s| s.to_string()).collect();
+        parse_args(&args)
+    }
+
+    // A file in the repository, wherever the tests are run from.
+    fn repo_file(path: &str) -> String {
+        format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path)
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            args("scan a.ssc").unwrap().command,
+            Command::Scan(vec!["a.ssc".to_string()])
+        );
+        assert_eq!(args("check").unwrap().command, Command::Check(None));
+        assert_eq!(
+            args("check g.bnf").unwrap().command,
+            Command::Check(Some("g.bnf".to_string()))
+        );
+        assert_eq!(
+            args("dot pda").unwrap().command,
+            Command::Dot("pda".to_string())
+        );
+        assert_eq!(
+            args("analyze a.ssc b.ssc").unwrap().command,
+            Command::Analyze(vec!["a.ssc".to_string(), "b.ssc".to_string()])
+        );
+        assert_eq!(
+            args("symbols a.ssc --format csv --sort kind").unwrap().sort,
+            SymbolOrder::Kind
+        );
+        assert_eq!(
+            args("xref a.ssc").unwrap().command,
+            Command::Xref("a.ssc".to_string())
+        );
+        assert_eq!(args("table --help").unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn test_options_can_go_anywhere() {
+        let options = args("-q parse --trace csv a.ssc -o out.txt --grammar g.bnf").unwrap();
+
+        assert_eq!(options.command, Command::Parse(vec!["a.ssc".to_string()]));
+        assert_eq!(options.verbosity, Verbosity::Quiet);
+        assert_eq!(options.trace, "csv");
+        assert_eq!(options.output, Some("out.txt".to_string()));
+        assert_eq!(options.grammar, Some("g.bnf".to_string()));
+        assert_eq!(
+            args("--log pda=debug parse a.ssc").unwrap().log,
+            Some(Filter::parse("pda=debug").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_a_lone_filename_prints_everything() {
+        let options = args("a.ssc").unwrap();
+
+        assert_eq!(options.command, Command::Parse(vec!["a.ssc".to_string()]));
+        assert_eq!(options.verbosity, Verbosity::Verbose);
+        assert_eq!(args("parse a.ssc").unwrap().verbosity, Verbosity::Normal);
+    }
+
+    #[test]
+    fn test_several_paths() {
+        assert_eq!(
+            args("parse - a.ssc samples").unwrap().command,
+            Command::Parse(vec![
+                "-".to_string(),
+                "a.ssc".to_string(),
+                "samples".to_string()
+            ])
+        );
+
+        let (sources, walked) = expand_paths(&["-".to_string(), "samples".to_string()]).unwrap();
+        assert!(walked);
+        assert_eq!(sources[0], "-");
+        assert!(sources.len() > 2);
+        assert!(sources[1..].iter().all(|path| path.ends_with(".ssc")));
+        assert!(sources[1..].windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut summary = Summary::default();
+        summary.add(2, true, &[]);
+        let result = parser::parse("package 1a\n$\n");
+        summary.add(2, false, result.diagnostics());
+
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.accepted, 1);
+        assert_eq!(summary.lexical_errors, 1);
+        assert_eq!(summary.syntax_errors, 1);
+        assert_eq!(
+            summary.to_string(),
+            "2 files, 4 lines: 1 accepted, 1 rejected\n1 lexical error, 1 syntax error\n"
+        );
+
+        let result =
+            parser::parse("private class { print (a); val b : int; { val b : int; }; }\n$\n");
+        summary.add(1, false, result.semantic_diagnostics());
+        assert_eq!(summary.semantic_errors, 1);
+        assert_eq!(summary.warnings, 1);
+        assert!(summary
+            .to_string()
+            .ends_with("1 semantic error, 1 warning\n"));
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count(0, "error"), "0 errors");
+        assert_eq!(count(1, "error"), "1 error");
+        assert_eq!(count(2, "error"), "2 errors");
+    }
+
+    #[test]
+    fn test_analyze_with_the_builtin_grammar_file() {
+        // The grammar file has the same rules as the built-in grammar, so the source can be analyzed, and its undeclared `x` found.
+        let options = parse_args(&[
+            "-q".to_string(),
+            "-g".to_string(),
+            repo_file("grammars/simple_scala.bnf"),
+            "analyze".to_string(),
+            repo_file("samples/new_example.ssc"),
+        ])
+        .unwrap();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        assert_eq!(run(&options, &mut out, &mut err).unwrap(), EXIT_ERRORS);
+        assert!(out.is_empty());
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.contains("error: Undeclared identifier `x`"));
+        assert!(err.contains("samples/new_example.ssc:10:7"));
+    }
+
+    #[test]
+    fn test_xref_with_the_builtin_grammar_file() {
+        let options = parse_args(&[
+            "-g".to_string(),
+            repo_file("grammars/simple_scala.bnf"),
+            "xref".to_string(),
+            repo_file("samples/new_example.ssc"),
+        ])
+        .unwrap();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        assert_eq!(run(&options, &mut out, &mut err).unwrap(), EXIT_ERRORS);
+        // The function `x` is declared and used in the first class, and the `x` in the object is never declared.
+        let out = String::from_utf8(out).unwrap();
+        let x: Vec<&str> = out.lines().filter(|line| line.starts_with("x ")).collect();
+        assert_eq!(x.len(), 2);
+        assert!(x[0].contains("| function ") && x[0].ends_with("| 7:44"));
+        assert!(x[1].contains("| identifier ") && x[1].ends_with("| 10:7, 15:24"));
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.contains("error: Undeclared identifier `x`"));
+    }
+
+    #[test]
+    fn test_a_file_that_cannot_be_read_is_not_fatal() {
+        let options = args("parse no/such/file.ssc").unwrap();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        match run(&options, &mut out, &mut err) {
+            Err(Failure::Message(message)) => assert!(message.starts_with("Couldn't read")),
+            result => panic!("expected a failure, got {:?}", result),
+        }
+    }
 
-    // Print out the contents of the symbol table.
-    println!("{}", "Symbol table contents:".blue().bold());
-    // Table header
-    println!(
-        "{0: <30} | {1: <30} | {2: <}",
-        "Token", "Symbol Type", "Code"
-    );
-    for symbol in parser.scanner.symtab.symbols {
-        println!("{}", symbol);
+    #[test]
+    fn test_bad_arguments() {
+        assert!(args("").is_err());
+        assert!(args("scan").is_err());
+        assert!(args("table a.ssc").is_err());
+        assert!(args("dot nfa").is_err());
+        assert!(args("xref a.ssc b.ssc").is_err());
+        assert!(args("--trace xml parse a.ssc").is_err());
+        assert!(args("--format json parse a.ssc").is_err());
+        assert!(args("--format csv tree a.ssc").is_err());
+        assert_eq!(args("--format dot tree a.ssc").unwrap().format, "dot");
+        assert!(args("--format dot symbols a.ssc").is_err());
+        assert_eq!(args("--format csv symbols a.ssc").unwrap().format, "csv");
+        assert!(args("--format json analyze a.ssc").is_err());
+        assert!(args("--sort size symbols a.ssc").is_err());
+        assert_eq!(args("--format csv --help").unwrap().command, Command::Help);
+        assert!(args("parse a.ssc --grammar").is_err());
+        assert!(args("--frobnicate a.ssc").is_err());
+        assert!(args("--log loud parse a.ssc").is_err());
+        assert!(args("-j 0 parse a.ssc").is_err());
+        assert!(args("a.ssc b.ssc").is_err());
     }
 }
@@ -1,105 +1,303 @@
 #![warn(clippy::all)]
 
+use std::sync::Arc;
+
+use crate::ast::{self, Program};
 use crate::bookkeeper::{Bookkeeper, SymbolType, Token};
-use crate::error::Error;
-use crate::pda::{is_terminal_symbol, PDA};
-use crate::rules::EPSILON_CODE;
+use crate::derivation::Derivation;
+use crate::diagnostic::{Diagnostic, Span};
+use crate::error::{Error, ErrorType};
+use crate::grammar::{Grammar, GrammarReport};
+use crate::log::{log, Level};
+use crate::pda::{ParsingTables, PDA};
 use crate::scanner::Scanner;
-use crate::DEBUG;
+use crate::symbol::Terminal;
+use crate::trace::{SilentTrace, Trace};
+use crate::tree::ParseTree;
 
 pub struct Parser {
     pub(crate) scanner: Scanner,
     lookahead: Option<Token>,
     pda: PDA,
+    pub(crate) errors: Vec<Diagnostic>, // Every lexical and syntax error found in the source, in order.
+    pub(crate) file: String,            // The name of the source file, for use in diagnostics.
 }
 
 impl Parser {
     pub fn new(src: String, symtab: Bookkeeper) -> Self {
+        Parser::with_pda(src, symtab, PDA::new())
+    }
+
+    // Create a parser for another grammar than the built-in one, such as one loaded from a grammar file.
+    // If the grammar is not LL(1), there is no parser for it, only the report of what is wrong with it.
+    pub fn with_grammar(
+        src: String,
+        symtab: Bookkeeper,
+        grammar: &Grammar,
+    ) -> Result<Self, Box<GrammarReport>> {
+        PDA::with_grammar(grammar).map(|pda| Parser::with_pda(src, symtab, pda))
+    }
+
+    // Create a parser driven by tables that have already been built. Building the tables for a grammar takes a while, so parsers for many sources, such as those in a batch, should share them.
+    pub fn with_tables(src: String, symtab: Bookkeeper, tables: Arc<ParsingTables>) -> Self {
+        Parser::with_pda(src, symtab, PDA::with_tables(tables))
+    }
+
+    fn with_pda(src: String, symtab: Bookkeeper, pda: PDA) -> Self {
         let scanner: Scanner = Scanner::new(src, symtab);
-        let pda = PDA::new();
 
         Parser {
             scanner,
             lookahead: None,
             pda,
+            errors: Vec::new(),
+            file: "<source>".to_string(),
         }
     }
 
-    // Parse the source. If the parsing is successful, return true. If not, return false.
+    // Parse the source without printing anything. If the parsing is successful, return true. If not, return false.
+    // Parsing does not stop at the first error; every error found along the way is kept in `errors`. To see the steps, use `parse_with` and a `Trace`.
     pub fn parse(&mut self) -> bool {
-        // Print out the table header for the parse output
-        println!(
-            "{0: <30} | {1: <30} | {2: <30} | {3: <}",
-            "Steps", "Stack Top", "Lookahead", "Action"
-        );
+        self.parse_with(&mut SilentTrace)
+    }
 
-        self.pda.initialize();
+    // Parse the source, and tell the trace about every step instead of printing it.
+    pub fn parse_with(&mut self, trace: &mut dyn Trace) -> bool {
+        trace.begin();
+        self.pda.initialize(trace);
         let mut needs_new_lookahead = true;
-        let mut token_request_result: (Option<&Token>, Option<&Error>, bool) = (None, None, false);
-        let mut scanner_is_done: bool = false;
-        while !self.pda.stack.is_empty() {
-            // First, we need to fetch a new lookahead token.
-            if needs_new_lookahead {
-                if DEBUG {
-                    println!("New lookahead needed, making token request.");
+        // While this is set, we are in panic mode: we have reported an error and are skipping ahead, so we do not report anything else until a terminal is matched again.
+        let mut recovering = false;
+        loop {
+            while !self.pda.stack.is_empty() {
+                // First, we need to fetch a new lookahead token.
+                if needs_new_lookahead {
+                    log!(Level::Trace, "requesting a new lookahead");
+                    self.next_lookahead();
+                    needs_new_lookahead = false;
+                }
+
+                // Run a transition of the PDA, and see whether a path to acceptance still exists.
+                let lookahead = self.lookahead.to_owned().unwrap();
+                let transition_result = self.pda.transition(lookahead.clone(), trace);
+                if !transition_result.0 && !recovering {
+                    self.report(
+                        syntax_error(&lookahead),
+                        self.pda.expected_terminals(self.pda.stack_top),
+                    );
+                    recovering = true;
+                } else if transition_result.0 && transition_result.1 {
+                    recovering = false;
+                }
+
+                // Determine whether we need a new lookahead token.
+                if lookahead.terminal != Terminal::EndOfInput && transition_result.1 {
+                    // consume the symbol, reset the lookahead
+                    needs_new_lookahead = true;
                 }
-                token_request_result = self.scanner.token_request().to_owned();
+            }
+
+            if needs_new_lookahead {
+                self.next_lookahead();
                 needs_new_lookahead = false;
             }
 
+            // The stack is empty. If the input is too, we are done.
+            let lookahead = self.lookahead.to_owned().unwrap();
+            if lookahead.terminal == Terminal::EndOfInput {
+                break;
+            }
+
+            // Otherwise, there is input left over. Report it, skip ahead to the next class or object body, and carry on parsing from there.
+            // A grammar without a <scala-body> has nowhere to carry on from, so we stop.
+            if !recovering {
+                self.report(
+                    syntax_error(&lookahead),
+                    self.pda.expected_terminals(self.pda.stack_top),
+                );
+                recovering = true;
+            }
+            let (resume_at, first) = match self.pda.tables.recovery.clone() {
+                Some(recovery) => recovery,
+                None => break,
+            };
+            while !first.contains(&self.lookahead.as_ref().unwrap().terminal) {
+                if self.lookahead.as_ref().unwrap().terminal == Terminal::EndOfInput {
+                    break;
+                }
+                self.next_lookahead();
+            }
+            if self.lookahead.as_ref().unwrap().terminal == Terminal::EndOfInput {
+                break;
+            }
+            log!(
+                Level::Debug,
+                "picking the parsing back up on line {}",
+                self.lookahead.as_ref().unwrap().line_number
+            );
+            self.pda.resume(resume_at, trace);
+        }
+
+        let ret: bool = self.pda.q && self.errors.is_empty();
+        trace.end(ret);
+
+        ret
+    }
+
+    // Request tokens from the scanner until we get one that is not a lexical error, and make it the lookahead.
+    // Lexical errors are recorded along the way. Once the scanner runs out of tokens, the lookahead is epsilon.
+    fn next_lookahead(&mut self) {
+        loop {
+            let token_request_result = self.scanner.token_request();
+
             // If we have no error, and if we do in fact get a token from the request.
-            if token_request_result.to_owned().1.is_none() {
-                if token_request_result.to_owned().0.is_some() {
-                    self.lookahead = Some(token_request_result.0.unwrap().to_owned());
+            if token_request_result.1.is_none() {
+                if let Some(tkn) = token_request_result.0 {
+                    self.lookahead = Some(tkn.to_owned());
                 } else {
                     // Handle the epsilon case
-                    // Just create some filler stuff. We will only use the code, and that's fine.
+                    // Just create some filler stuff. We will mostly use the code, and place it just after the last token that we matched so that errors at the end of the input point somewhere useful.
+                    let (line_number, column, span) = match self.pda.matched.last() {
+                        Some(last) => (
+                            last.line_number,
+                            last.end_column,
+                            Span::new(last.span.end, last.span.end),
+                        ),
+                        None => (1, 1, Span::default()),
+                    };
                     self.lookahead = Some(Token {
                         token: "epsilon".to_string(),
                         symbol_type: SymbolType::Epsilon,
-                        line_number: 0,
-                        code: EPSILON_CODE,
+                        line_number,
+                        column,
+                        end_column: column,
+                        span,
+                        terminal: Terminal::EndOfInput,
                     });
                 }
 
-                if DEBUG {
-                    dbg!(&self.lookahead);
-                }
+                let lookahead = self.lookahead.as_ref().unwrap();
+                log!(
+                    Level::Debug,
+                    "lookahead {} `{}` on line {}",
+                    lookahead.terminal.name(),
+                    lookahead.token,
+                    lookahead.line_number
+                );
+                return;
+            }
 
-                // Run a transition of the PDA, and see whether a path to acceptance still exists.
-                let transition_result = self.pda.transition(self.lookahead.to_owned().unwrap());
-                if !transition_result.0 {
-                    println!("REJECT");
-                    return false;
-                }
+            let error = token_request_result.1.unwrap().to_owned();
+            self.report(error, Vec::new());
+        }
+    }
 
-                // Determine whether we need a new lookahead token.
-                let symbol_code = self.lookahead.as_ref().unwrap().code;
-                if is_terminal_symbol(symbol_code) && transition_result.1 {
-                    // consume the symbol, reset the lookahead
-                    needs_new_lookahead = true;
-                }
-            }
+    // Record an error as a diagnostic, along with the terminals that we expected to find instead.
+    fn report(&mut self, error: Error, expected: Vec<Terminal>) {
+        let diagnostic = Diagnostic::new(&self.file, error, expected);
+        log!(
+            Level::Info,
+            "{} `{}` on line {}",
+            diagnostic.error_type(),
+            diagnostic.token(),
+            diagnostic.line_number()
+        );
+        self.errors.push(diagnostic);
+    }
 
-            // Keep track of whether the scanner is done.
-            scanner_is_done = token_request_result.to_owned().2;
+    // Parse the source, and build its abstract syntax tree if the parsing is successful.
+    // The tree can only be built for the built-in grammar, since it is built from the rules that were used.
+    pub fn parse_program(&mut self) -> Option<Program> {
+        if !self.pda.tables.is_builtin() || !self.parse() {
+            return None;
         }
 
-        let ret: bool = self.pda.q && self.pda.stack.is_empty() && scanner_is_done;
-        if ret {
-            println!("ACCEPT");
-        } else {
-            println!("REJECT");
+        self.program()
+    }
+
+    // Build the abstract syntax tree of a source that has already been parsed successfully with the built-in grammar.
+    pub fn program(&self) -> Option<Program> {
+        if !self.pda.tables.is_builtin() || !self.pda.q || !self.errors.is_empty() {
+            return None;
         }
 
-        ret
+        Some(ast::build(&self.pda.derivation, &self.pda.matched))
+    }
+
+    // The leftmost derivation of a source that has been parsed successfully.
+    pub fn derivation(&self) -> Option<Derivation> {
+        if !self.pda.q || !self.errors.is_empty() {
+            return None;
+        }
+
+        Some(Derivation {
+            start: self.pda.tables.start,
+            rules: self.pda.derivation.clone(),
+            expansion_rules: self.pda.tables.expansion_rules.clone(),
+            names: (*self.pda.tables.names).clone(),
+        })
+    }
+
+    // The concrete parse tree, as far as the parsing has got.
+    pub fn tree(&self) -> &ParseTree {
+        &self.pda.tree
+    }
+
+    // Every lexical and syntax error found so far, in order.
+    pub fn errors(&self) -> &[Diagnostic] {
+        &self.errors
+    }
+
+    // The symbol table, with every identifier and constant scanned so far.
+    pub fn symtab(&self) -> &Bookkeeper {
+        &self.scanner.symtab
+    }
+
+    // Set the name of the source file, for use in diagnostics.
+    pub fn set_file(&mut self, file: &str) {
+        self.file = file.to_string();
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+}
+
+// Create a syntax error for an unexpected lookahead token.
+fn syntax_error(lookahead: &Token) -> Error {
+    let token = if lookahead.terminal == Terminal::EndOfInput {
+        "end of input".to_string()
+    } else {
+        lookahead.token.clone()
+    };
+
+    Error {
+        error_type: ErrorType::SyntaxError,
+        token,
+        line_number: lookahead.line_number,
+        column: lookahead.column,
+        span: lookahead.span,
     }
 }
 
 #[cfg(test)]
 mod parser_tests {
     use crate::bookkeeper::Bookkeeper;
+    use crate::error::ErrorType;
     use crate::parser::Parser;
+    use crate::symbol::Terminal;
+
+    // A parser can be sent to another thread, and the tables that drive it can be shared between threads.
+    #[test]
+    fn test_thread_safety() {
+        fn send<T: Send>() {}
+        fn share<T: Send + Sync>() {}
+
+        send::<Parser>();
+        send::<crate::scanner::Scanner>();
+        share::<crate::pda::ParsingTables>();
+        share::<crate::grammar::Grammar>();
+    }
 
     // Initialize the parser
     fn init(src: String) -> Parser {
@@ -164,6 +362,162 @@ mod parser_tests {
         assert!(p.parse());
     }
 
+    #[test]
+    fn test_errors_are_empty_on_accept() {
+        let mut p = init("package a;\nimport b;\n$\n".to_string());
+
+        assert!(p.parse());
+        assert!(p.errors.is_empty());
+    }
+
+    #[test]
+    fn test_every_syntax_error_is_reported() {
+        let src_str = "abstract class {
+        val a : int;
+        a <= ;
+        b <= 5;
+        c <= + 3;
+        }
+        $
+        "
+        .to_string();
+        let mut p = init(src_str);
+
+        assert!(!p.parse());
+
+        let lines: Vec<usize> = p.errors.iter().map(|e| e.line_number).collect();
+        assert_eq!(lines, vec![3, 5]);
+        assert!(p
+            .errors
+            .iter()
+            .all(|e| e.error_type == ErrorType::SyntaxError));
+    }
+
+    #[test]
+    fn test_parsing_resumes_at_the_next_body() {
+        let src_str = "abstract class { a <= 1; } }
+        private object { b <= 2 }
+        $
+        "
+        .to_string();
+        let mut p = init(src_str);
+
+        assert!(!p.parse());
+
+        let tokens: Vec<&str> = p.errors.iter().map(|e| e.token.as_str()).collect();
+        assert_eq!(tokens, vec!["}", "}"]);
+        assert_eq!(p.errors[1].line_number, 2);
+    }
+
+    #[test]
+    fn test_lexical_errors_do_not_stop_the_parser() {
+        let mut p = init("package 1x;\nimport 2.3.4;\n$\n".to_string());
+
+        assert!(!p.parse());
+
+        let error_types: Vec<ErrorType> = p.errors.iter().map(|e| e.error_type.clone()).collect();
+        assert_eq!(
+            error_types,
+            vec![
+                ErrorType::IdentifierBeginsWithNumber,
+                ErrorType::SyntaxError,
+                ErrorType::ConstantHasTooManyPeriods,
+                ErrorType::SyntaxError,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_semicolon_at_end_of_input() {
+        let mut p = init("package a\n$\n".to_string());
+
+        assert!(!p.parse());
+        assert_eq!(p.errors.len(), 1);
+        assert_eq!(p.errors[0].token, "end of input");
+        assert_eq!(p.errors[0].expected, vec![Terminal::Semicolon]);
+        // The error points just past the last token that was matched.
+        assert_eq!(p.errors[0].line_number, 1);
+        assert_eq!(p.errors[0].column, 10);
+    }
+
+    #[test]
+    fn test_diagnostic_points_at_the_token() {
+        let src_str = "abstract class {\n    a <= ;\n}\n$\n".to_string();
+        let mut p = init(src_str);
+
+        assert!(!p.parse());
+
+        let diagnostic = &p.errors[0];
+        assert_eq!(diagnostic.line_number, 2);
+        assert_eq!(diagnostic.column, 10);
+        assert_eq!(
+            diagnostic.expected,
+            vec![
+                Terminal::Id,
+                Terminal::Const,
+                Terminal::Not,
+                Terminal::True,
+                Terminal::False,
+                Terminal::LeftParen,
+                Terminal::At
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grammar_file() {
+        let grammar = crate::bnf::parse(
+            "<program> ::= { <var> }
+             <var> ::= \"val\" \"[id]\" [ \":\" <type> ] \";\"
+             <type> ::= \"int\" | \"real\" | \"bool\"
+            ",
+        )
+        .unwrap();
+
+        let mut p = Parser::with_grammar(
+            "val a; val b : int;\n".to_string(),
+            Bookkeeper::new(),
+            &grammar,
+        )
+        .unwrap();
+        assert!(p.parse());
+        assert!(p.parse_program().is_none());
+
+        let mut p =
+            Parser::with_grammar("val a : ;\n".to_string(), Bookkeeper::new(), &grammar).unwrap();
+        assert!(!p.parse());
+        assert_eq!(p.errors[0].token, ";");
+        assert_eq!(
+            p.errors[0].expected,
+            vec![Terminal::Int, Terminal::Real, Terminal::Bool]
+        );
+    }
+
+    #[test]
+    fn test_grammar_that_is_not_ll1_has_no_parser() {
+        let grammar = crate::bnf::parse("<scala> ::= <scala> \"[id]\" | \"package\"\n").unwrap();
+
+        match Parser::with_grammar("package\n".to_string(), Bookkeeper::new(), &grammar) {
+            Ok(_) => panic!("the grammar is left-recursive"),
+            Err(report) => assert!(!report.is_ll1()),
+        }
+    }
+
+    #[test]
+    fn test_builtin_grammar_file_parses_the_same() {
+        let grammar = crate::bnf::parse(include_str!("../grammars/simple_scala.bnf")).unwrap();
+        let src_str = "abstract class { a <= 1; } }
+        private object { b <= 2 }
+        $
+        ";
+
+        let mut builtin = init(src_str.to_string());
+        let mut loaded =
+            Parser::with_grammar(src_str.to_string(), Bookkeeper::new(), &grammar).unwrap();
+        assert_eq!(builtin.parse(), loaded.parse());
+        assert_eq!(builtin.errors, loaded.errors);
+    }
+
     #[test]
     fn test_dr_kim_source_program() {
         let src_str = "package a;
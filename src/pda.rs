@@ -1,184 +1,389 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
 use crate::bookkeeper::Token;
-use crate::rules::{code_to_string, EPSILON_CODE, EXPANSION_RULES, PARSING_RULES, START_SYMBOL};
+use crate::grammar::{Grammar, GrammarReport};
+use crate::log::{log, Level};
+use crate::rules::{EXPANSION_RULES, FIRST_MODIFIER, FOLLOW_SETS, PARSING_RULES, START_SYMBOL};
 use crate::stack::Stack;
-use crate::DEBUG;
+use crate::symbol::{NonTerminal, Symbol, Terminal};
+use crate::trace::{Action, Step, Trace};
+use crate::tree::ParseTree;
+
+// The tables that drive a PDA for one grammar, in the same form as PARSING_RULES, EXPANSION_RULES and FOLLOW_SETS.
+// They never change once they are built, so every parser for the same grammar can share them, on as many threads as it likes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsingTables {
+    pub(crate) start: NonTerminal,
+    pub(crate) parsing_rules: HashMap<(NonTerminal, Terminal), u16>,
+    pub(crate) expansion_rules: HashMap<u16, Vec<Symbol>>,
+    pub(crate) follow_sets: HashMap<NonTerminal, HashSet<Terminal>>,
+    pub(crate) names: Arc<BTreeMap<NonTerminal, String>>, // The names of any nonterminals that are not in the built-in grammar.
+    // Where to pick the parsing back up if the stack is emptied before the input is: a nonterminal, and the terminals that can begin it.
+    pub(crate) recovery: Option<(NonTerminal, HashSet<Terminal>)>,
+    builtin: bool, // Whether these are the rules of the built-in grammar, worked out once when the tables are built.
+}
+
+lazy_static! {
+    // The tables of the built-in grammar, which every parser for it shares.
+    static ref BUILTIN_TABLES: Arc<ParsingTables> = Arc::new(ParsingTables {
+        start: START_SYMBOL,
+        parsing_rules: PARSING_RULES.clone(),
+        expansion_rules: EXPANSION_RULES.clone(),
+        follow_sets: FOLLOW_SETS.clone(),
+        names: Arc::new(BTreeMap::new()),
+        recovery: Some((NonTerminal::SCALA_BODY, FIRST_MODIFIER.clone())),
+        builtin: true,
+    });
+}
+
+impl ParsingTables {
+    // The hand-written tables of the built-in grammar, from rules.rs.
+    pub fn builtin() -> Arc<ParsingTables> {
+        BUILTIN_TABLES.clone()
+    }
+
+    // The tables for any grammar, such as one loaded from a grammar file, computed from its productions.
+    // A grammar with LL(1) conflicts or left recursion cannot drive a PDA, so what is wrong with it is returned instead.
+    pub fn new(grammar: &Grammar) -> Result<Self, Box<GrammarReport>> {
+        let analysis = grammar.analyze();
+        let report = grammar.check_with(&analysis);
+        if !report.is_ll1() {
+            return Err(Box::new(report));
+        }
+        let expansion_rules: HashMap<u16, Vec<Symbol>> = grammar
+            .productions
+            .iter()
+            .map(|(rule, production)| (*rule, production.body.clone()))
+            .collect();
+        // A grammar file with the same rules as the built-in grammar gets the same abstract syntax tree.
+        let builtin = expansion_rules == *EXPANSION_RULES;
+
+        Ok(ParsingTables {
+            start: grammar.start,
+            // There are no conflicts, so every entry has exactly one rule.
+            parsing_rules: grammar
+                .predictions(&analysis)
+                .into_iter()
+                .map(|(key, rules)| (key, *rules.iter().next().unwrap()))
+                .collect(),
+            expansion_rules,
+            follow_sets: analysis
+                .follow
+                .iter()
+                .map(|(nonterminal, follow)| (*nonterminal, follow.iter().copied().collect()))
+                .collect(),
+            names: Arc::new(grammar.names.clone()),
+            // Only a grammar with a <scala-body> can be picked back up part way through.
+            recovery: analysis
+                .first
+                .get(&NonTerminal::SCALA_BODY)
+                .map(|first| (NonTerminal::SCALA_BODY, first.iter().copied().collect())),
+            builtin,
+        })
+    }
+
+    // Whether these are the tables of the built-in grammar, which the abstract syntax tree is built for.
+    pub fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+}
 
 #[allow(clippy::upper_case_acronyms)]
 pub struct PDA {
     pub(crate) q: bool, // the one state that we have. we can only accept if this is set to true.
     step: u32,          // What step in the computation we are at.
     pub(crate) stack: Stack,
+    pub(crate) derivation: Vec<u16>, // The rules that we have applied, in order. This is the leftmost derivation of the input.
+    pub(crate) matched: Vec<Token>,  // The terminals that we have matched, in order.
+    pub(crate) stack_top: Symbol,    // The stack top that the last transition was run on.
+    pub(crate) tables: Arc<ParsingTables>,
+    pub(crate) tree: ParseTree, // The parse tree, as far as it has been built.
+    nodes: Vec<usize>, // The node of the parse tree for each symbol on the stack, from the bottom up.
 }
 
 impl PDA {
+    // Create a PDA for the built-in grammar, driven by the hand-written tables in rules.rs.
     pub fn new() -> Self {
+        PDA::with_tables(ParsingTables::builtin())
+    }
+
+    // Create a PDA for any grammar, such as one loaded from a grammar file, with the tables computed from its productions.
+    // A grammar with LL(1) conflicts or left recursion cannot drive the PDA, so what is wrong with it is returned instead.
+    pub fn with_grammar(grammar: &Grammar) -> Result<Self, Box<GrammarReport>> {
+        ParsingTables::new(grammar).map(|tables| PDA::with_tables(Arc::new(tables)))
+    }
+
+    // Create a PDA driven by tables that have already been built.
+    pub fn with_tables(tables: Arc<ParsingTables>) -> Self {
         let stack: Stack = Stack::new();
 
         PDA {
             q: false,
             step: 1,
             stack,
+            derivation: Vec::new(),
+            matched: Vec::new(),
+            stack_top: Symbol::Bottom,
+            tables,
+            tree: ParseTree::new(),
+            nodes: Vec::new(),
         }
     }
 
     // Initialize the PDA by pushing the start symbol onto the stack.
-    pub fn initialize(&mut self) {
-        self.stack.push(START_SYMBOL); // 42, aka <scala>, is the start symbol in our grammar.
+    pub fn initialize(&mut self, trace: &mut dyn Trace) {
+        self.stack.push(self.tables.start.into()); // 42, aka <scala>, is the start symbol in the built-in grammar.
+        let root = self.tree.add_root(
+            self.tables.start.into(),
+            self.symbol_name(self.tables.start.into()),
+        );
+        self.nodes.push(root);
         self.q = true;
 
-        print_step(
-            self.step,
-            "z0 (0)".to_string(),
-            "None".to_string(),
-            "Push start symbol.".to_string(),
-        );
-        self.step += 1;
+        self.emit_step(trace, Symbol::Bottom, None, Action::PushStart);
+    }
+
+    // Resume the parsing from the given nonterminal, after the stack has been emptied by an error.
+    pub fn resume(&mut self, nonterminal: NonTerminal, trace: &mut dyn Trace) {
+        self.stack.push(nonterminal.into());
+        let root = self
+            .tree
+            .add_root(nonterminal.into(), self.symbol_name(nonterminal.into()));
+        self.nodes.push(root);
+
+        self.emit_step(trace, Symbol::Bottom, None, Action::Resume(nonterminal));
     }
 
     // Run an iteration of the transition function.
-    // Return whether the parsing can continue with a path towards acceptance, because we will want to reject as soon as we can.
-    // The second entry in the tuple is whether or not a new lookahead token needs to be requested.
-    pub fn transition(&mut self, lookahead: Token) -> (bool, bool) {
-        // Pop the stack, create a default action message (error)
+    // Return whether the parsing can continue with a path towards acceptance. If it cannot, we recover from the error in panic mode so that the parsing can still go on to find any further errors.
+    // The second entry in the tuple is whether or not a new lookahead token needs to be requested, either because it was matched or because panic mode skipped it.
+    pub fn transition(&mut self, lookahead: Token, trace: &mut dyn Trace) -> (bool, bool) {
+        // Pop the stack. Every path below records what it did in the action.
         let stack_top = self.stack.pop();
-        let mut action: String = "ERROR".to_string();
+        let node = self.nodes.pop().unwrap();
+        self.stack_top = stack_top;
+        let action: Action;
         let mut ret: (bool, bool) = (false, false);
-        if DEBUG {
-            dbg!(stack_top);
-            dbg!(&lookahead);
-            dbg!(ret);
-        }
-        if !is_terminal_symbol(stack_top) {
+        if let Symbol::NonTerminal(nonterminal) = stack_top {
             // Get our parsing rules, which we need to do first before we get our expansion rules.
-            let rule = PARSING_RULES.get(&(stack_top, lookahead.code));
-            if DEBUG {
-                dbg!(rule);
-            }
-            if let Some(..) = rule {
-                let tokens = EXPANSION_RULES.get(rule.unwrap()).unwrap().to_owned();
-                action = format!("Use rule {}.", rule.unwrap());
-
-                // Push the required tokens onto the stack in reverse order.
-                for code in tokens.iter().rev() {
-                    self.stack.push(code.to_owned());
-                }
+            let rule = self
+                .tables
+                .parsing_rules
+                .get(&(nonterminal, lookahead.terminal));
+            if let Some(rule) = rule {
+                log!(
+                    Level::Debug,
+                    "{} on {}: rule {}",
+                    self.symbol_name(stack_top),
+                    lookahead.terminal.name(),
+                    rule
+                );
+                action = Action::UseRule(*rule);
+                self.expand(node, *rule);
                 ret.0 = true;
             } else {
-                if DEBUG {
-                    println!(
-                        "Checking for an epsilon rule for non-terminal {}.",
-                        stack_top
-                    );
-                }
-                let epsilon_rule = PARSING_RULES.get(&(stack_top, EPSILON_CODE));
-
-                if DEBUG {
-                    dbg!(epsilon_rule);
-                }
-
-                if let Some(..) = epsilon_rule {
-                    action = format!("Use rule {}.", epsilon_rule.unwrap());
-                    let tokens = EXPANSION_RULES
-                        .get(epsilon_rule.unwrap())
-                        .unwrap()
-                        .to_owned();
+                let epsilon_rule = self
+                    .tables
+                    .parsing_rules
+                    .get(&(nonterminal, Terminal::EndOfInput));
 
-                    // Push the required tokens onto the stack in reverse order.
-                    for code in tokens.iter().rev() {
-                        self.stack.push(code.to_owned());
-                    }
+                if let Some(epsilon_rule) = epsilon_rule {
+                    log!(
+                        Level::Debug,
+                        "{} on {}: no rule, so the rule for the end of the input, {}",
+                        self.symbol_name(stack_top),
+                        lookahead.terminal.name(),
+                        epsilon_rule
+                    );
+                    action = Action::UseRule(*epsilon_rule);
+                    self.expand(node, *epsilon_rule);
                     ret.0 = true;
+                } else if lookahead.terminal == Terminal::EndOfInput
+                    || self
+                        .tables
+                        .follow_sets
+                        .get(&nonterminal)
+                        .unwrap()
+                        .contains(&lookahead.terminal)
+                {
+                    // Panic mode: the lookahead can follow this nonterminal, so we give up on the nonterminal (it stays popped) and carry on from there.
+                    log!(
+                        Level::Debug,
+                        "{} on {}: no rule, but it can follow, so give up on {}",
+                        self.symbol_name(stack_top),
+                        lookahead.terminal.name(),
+                        self.symbol_name(stack_top)
+                    );
+                    action = Action::ErrorPopStackTop;
+                } else {
+                    // Panic mode: the lookahead is of no use to us, so skip it and try this nonterminal again with the next one.
+                    log!(
+                        Level::Debug,
+                        "{} on {}: no rule, so skip {}",
+                        self.symbol_name(stack_top),
+                        lookahead.terminal.name(),
+                        lookahead.terminal.name()
+                    );
+                    self.stack.push(stack_top);
+                    self.nodes.push(node);
+                    action = Action::ErrorSkipLookahead;
+                    ret.1 = true;
                 }
             }
         // If the stack top isn't the lookahead, we cannot accept the string
-        } else if stack_top != lookahead.code {
+        } else if stack_top != Symbol::Terminal(lookahead.terminal) {
+            // Panic mode: act as if the missing terminal was there, by leaving it popped.
+            log!(
+                Level::Debug,
+                "{} on {}: missing {}",
+                self.symbol_name(stack_top),
+                lookahead.terminal.name(),
+                self.symbol_name(stack_top)
+            );
+            action = Action::ErrorPopStackTop;
             ret.0 = false;
         } else {
             // On the other hand, if the two are equal, then we consume, and will need a new lookahead token.
-            if DEBUG {
-                println!("MATCH.");
-            }
-            action = "Match.".to_string();
+            log!(Level::Debug, "{0} on {0}: match", lookahead.terminal.name());
+            action = Action::Match;
+            self.tree.nodes[node].token = Some(lookahead.clone());
+            self.matched.push(lookahead.clone());
             ret.0 = true;
             ret.1 = true;
         }
 
-        if DEBUG {
-            dbg!(ret);
-        }
-
-        // Print the parse output, with the following format:
-        // (Steps, stack top, lookahead, action)
-        print_step(
-            self.step,
-            code_to_string(stack_top),
-            format!("{} ({})", lookahead.token, lookahead.code),
-            action,
-        );
-        self.step += 1;
+        self.emit_step(trace, stack_top, Some(lookahead), action);
 
         ret
     }
-}
 
-// Print a step in the parse outupt.
-pub fn print_step(step: u32, stack_top: String, lookahead: String, action: String) {
-    println!(
-        "{0: <30} | {1: <30} | {2: <30} | {3: <}",
-        step.to_string(),
-        stack_top,
-        lookahead,
-        action
-    );
-}
+    // Replace the nonterminal on top of the stack, which has already been popped, with the body of the rule.
+    // The body becomes the children of the nonterminal's node in the parse tree, or a single epsilon leaf if it is empty.
+    fn expand(&mut self, node: usize, rule: u16) {
+        let tables = self.tables.clone();
+        let tokens = &tables.expansion_rules[&rule];
+        self.derivation.push(rule);
+        self.tree.nodes[node].rule = Some(rule);
 
-// Determine whether a symbol is terminal or nonterminal.
-pub fn is_terminal_symbol(code: u8) -> bool {
-    code <= 41 && code > 0
-}
+        let children: Vec<usize> = if tokens.is_empty() {
+            let epsilon = Symbol::Terminal(Terminal::EndOfInput);
+            vec![self.tree.add(epsilon, self.symbol_name(epsilon))]
+        } else {
+            tokens
+                .iter()
+                .map(|symbol| self.tree.add(*symbol, self.symbol_name(*symbol)))
+                .collect()
+        };
+        self.tree.nodes[node].children = children.clone();
 
-#[cfg(test)]
-mod is_terminal_symbol_tests {
-    use crate::pda::{is_terminal_symbol, EPSILON_CODE};
+        // Push the required tokens onto the stack in reverse order. An epsilon leaf is not pushed, since there is nothing to match.
+        for (symbol, child) in tokens.iter().zip(children).rev() {
+            self.stack.push(*symbol);
+            self.nodes.push(child);
+        }
+    }
 
-    #[test]
-    fn test_zero() {
-        assert!(!is_terminal_symbol(0));
+    // Tell the trace about the step that was just taken.
+    fn emit_step(
+        &mut self,
+        trace: &mut dyn Trace,
+        stack_top: Symbol,
+        lookahead: Option<Token>,
+        action: Action,
+    ) {
+        trace.step(&Step {
+            number: self.step,
+            stack_top,
+            lookahead,
+            action,
+            stack: self.stack.symbols().to_vec(),
+            names: self.tables.names.clone(),
+        });
+        self.step += 1;
     }
 
-    #[test]
-    fn test_one() {
-        assert!(is_terminal_symbol(1));
+    // The terminals that may be the lookahead when the given symbol is on top of the stack, in ascending order.
+    // For a nonterminal, this is its row of the parsing table. EndOfInput in the result stands for the end of the input.
+    pub fn expected_terminals(&self, stack_top: Symbol) -> Vec<Terminal> {
+        let nonterminal = match stack_top {
+            Symbol::NonTerminal(nonterminal) => nonterminal,
+            Symbol::Terminal(terminal) => return vec![terminal],
+            Symbol::Bottom => return Vec::new(),
+        };
+
+        let mut expected: Vec<Terminal> = self
+            .tables
+            .parsing_rules
+            .keys()
+            .filter(|(top, _)| *top == nonterminal)
+            .map(|(_, lookahead)| *lookahead)
+            .collect();
+        expected.sort_unstable();
+
+        expected
     }
 
-    #[test]
-    fn test_forty_one() {
-        assert!(is_terminal_symbol(41));
+    // Return the name of the symbol, without its code.
+    pub(crate) fn symbol_name(&self, symbol: Symbol) -> String {
+        symbol.name_in(&self.tables.names)
     }
+}
+
+#[cfg(test)]
+mod expected_terminals_tests {
+    use crate::grammar::Grammar;
+    use crate::pda::PDA;
+    use crate::symbol::Terminal::*;
+    use crate::symbol::{NonTerminal, Terminal};
 
     #[test]
-    fn test_forty_two() {
-        assert!(!is_terminal_symbol(42));
+    fn test_terminal_expects_itself() {
+        assert_eq!(
+            PDA::new().expected_terminals(Terminal::Semicolon.into()),
+            vec![Semicolon]
+        );
     }
 
     #[test]
-    fn test_forty_three() {
-        assert!(!is_terminal_symbol(43));
+    fn test_more_ids() {
+        assert_eq!(
+            PDA::new().expected_terminals(NonTerminal::MORE_IDS.into()),
+            vec![RightParen, Colon, Comma]
+        );
     }
 
     #[test]
-    fn test_sixty_nine() {
-        assert!(!is_terminal_symbol(69));
+    fn test_scala_body_expects_modifiers_or_end_of_input() {
+        assert_eq!(
+            PDA::new().expected_terminals(NonTerminal::SCALA_BODY.into()),
+            vec![Abstract, Final, Sealed, Private, Protected, EndOfInput]
+        );
     }
 
-    // Testing a couple of edge cases.
     #[test]
-    fn test_seventy() {
-        assert!(!is_terminal_symbol(70));
+    fn test_computed_tables_expect_the_same() {
+        let pda = PDA::with_grammar(&Grammar::builtin()).unwrap();
+
+        assert_eq!(
+            pda.expected_terminals(NonTerminal::MORE_IDS.into()),
+            vec![RightParen, Colon, Comma]
+        );
+        assert_eq!(
+            pda.expected_terminals(NonTerminal::SCALA_BODY.into()),
+            vec![Abstract, Final, Sealed, Private, Protected, EndOfInput]
+        );
+        assert_eq!(pda.tables.recovery, PDA::new().tables.recovery);
     }
 
     #[test]
-    fn test_epsilon() {
-        assert!(!is_terminal_symbol(EPSILON_CODE));
+    fn test_is_builtin() {
+        let file = crate::bnf::parse(include_str!("../grammars/simple_scala.bnf")).unwrap();
+        let other =
+            crate::bnf::parse("<list> ::= \"[id]\" <rest>\n<rest> ::= \",\" <list> | ε\n").unwrap();
+
+        assert!(PDA::new().tables.is_builtin());
+        assert!(PDA::with_grammar(&file).unwrap().tables.is_builtin());
+        assert!(!PDA::with_grammar(&other).unwrap().tables.is_builtin());
     }
 }
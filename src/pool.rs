@@ -0,0 +1,121 @@
+#![warn(clippy::all)]
+
+// A pool of worker threads, for checking many source programs at once.
+// The items are handed out one at a time, so that a slow one does not hold up the rest, but their results are handed back in the order of the items. That way the output is the same however the threads happen to be scheduled.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// The number of threads to use when none was asked for: one for each processor.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |jobs| jobs.get())
+}
+
+// Run `work` on every item, on up to `jobs` threads. Each result is handed to `done` on the calling thread, in the order of the items, as soon as it and every result before it are ready.
+// If `done` returns an error, the work stops and the error is returned.
+pub fn run_in_order<I, T, E, W, D>(items: &[I], jobs: usize, work: W, mut done: D) -> Result<(), E>
+where
+    I: Sync,
+    T: Send,
+    W: Fn(&I) -> T + Sync,
+    D: FnMut(&I, T) -> Result<(), E>,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                // Once there is nothing left, or nobody is listening, the worker is done.
+                if index >= items.len() || sender.send((index, work(&items[index]))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // The results that came back before one ahead of them, waiting for their turn.
+        let mut waiting: BTreeMap<usize, T> = BTreeMap::new();
+        let mut turn = 0;
+        for (index, result) in receiver.iter() {
+            waiting.insert(index, result);
+            while let Some(result) = waiting.remove(&turn) {
+                if let Err(error) = done(&items[turn], result) {
+                    // Stop handing out work, so that the workers finish what they have and go.
+                    next.store(items.len(), Ordering::Relaxed);
+                    return Err(error);
+                }
+                turn += 1;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod pool_tests {
+    use crate::pool::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_results_come_back_in_order() {
+        let items: Vec<u64> = (0..50).collect();
+        let mut results = Vec::new();
+
+        // The early items take the longest, so they finish last.
+        run_in_order(
+            &items,
+            8,
+            |item| {
+                thread::sleep(Duration::from_millis((50 - item) / 5));
+                item * 2
+            },
+            |item, result| {
+                results.push((*item, result));
+                Ok::<(), ()>(())
+            },
+        )
+        .unwrap();
+
+        let expected: Vec<(u64, u64)> = items.iter().map(|item| (*item, item * 2)).collect();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_an_error_stops_the_work() {
+        let items: Vec<u32> = (0..1000).collect();
+        let mut seen = 0;
+
+        let result = run_in_order(
+            &items,
+            4,
+            |item| *item,
+            |_, item| {
+                seen += 1;
+                if item == 10 {
+                    Err(item)
+                } else {
+                    Ok(())
+                }
+            },
+        );
+
+        assert_eq!(result, Err(10));
+        assert_eq!(seen, 11);
+    }
+
+    #[test]
+    fn test_no_items() {
+        let items: Vec<u32> = Vec::new();
+
+        let result = run_in_order(&items, 0, |item| *item, |_, _| Err(()));
+        assert_eq!(result, Ok(()));
+    }
+}
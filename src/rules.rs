@@ -1,232 +1,155 @@
 use std::collections::{HashMap, HashSet};
 
-// We are using this u8 code to represent epsilon in the rules that have it. This should be a last-resort consideration for transitions.
-pub const EPSILON_CODE: u8 = 253;
-pub const START_SYMBOL: u8 = 42;
-
-// Given a `u8` code, return the appropriate string form.
-// Example: 42 -> `<scala>`.
-pub fn code_to_string(code: u8) -> String {
-    let ret: &str = match code {
-        0 => "z0",
-        1 => "[id]",
-        2 => "[const]",
-        3 => "package",
-        4 => "import",
-        5 => "abstract",
-        6 => "final",
-        7 => "sealed",
-        8 => "private",
-        9 => "protected",
-        10 => "class",
-        11 => "object",
-        12 => "val",
-        13 => "def",
-        14 => "<=",
-        15 => "if",
-        16 => "else",
-        17 => "while",
-        18 => "case",
-        19 => "=>",
-        20 => "in",
-        21 => "print",
-        22 => "return",
-        23 => "not",
-        24 => "true",
-        25 => "false",
-        26 => "and",
-        27 => "or",
-        28 => "int",
-        29 => "real",
-        30 => "bool",
-        31 => ";",
-        32 => "{",
-        33 => "}",
-        34 => "(",
-        35 => ")",
-        36 => ":",
-        37 => ",",
-        38 => "=",
-        39 => "+",
-        40 => "*",
-        41 => "@",
-        42 => "<scala>",
-        43 => "<packages>",
-        44 => "<imports>",
-        45 => "<scala-body>",
-        46 => "<subbody>",
-        47 => "<modifier>",
-        48 => "<subbody-tail>",
-        49 => "<tail-type>",
-        50 => "<block>",
-        51 => "<stmts>",
-        52 => "<stmt>",
-        53 => "<dcl>",
-        54 => "<dcl-tail>",
-        55 => "<ids>",
-        56 => "<more-ids>",
-        57 => "<type>",
-        58 => "<asmt>",
-        59 => "<if>",
-        60 => "<while>",
-        61 => "<case>",
-        62 => "<in>",
-        63 => "<out>",
-        64 => "<return>",
-        65 => "<expr>",
-        66 => "<arith-expr>",
-        67 => "<arith>",
-        68 => "<bool-exp>",
-        69 => "<bool>",
-        _ => "{ERROR}",
-    };
+use crate::symbol::Symbol::{NonTerminal as N, Terminal as T};
+use crate::symbol::Terminal::*;
+use crate::symbol::{NonTerminal, Symbol, Terminal};
 
-    format!("{} ({})", ret, code)
-}
+// The start symbol of the built-in grammar.
+pub const START_SYMBOL: NonTerminal = NonTerminal::SCALA;
 
 // This is the static HashMap that we will use to store the LL(1) parsing rules.
-// The key is a tuple of u8s, like so: (top of stack, lookahead)
+// The key is a tuple, like so: (top of stack, lookahead)
 // The value is the id of the rule that we will need to use for the expansion. There are going to be a lot of combinations here, so try to use iterators wherever possible since we will only have to perform this operation once per runtime.
 lazy_static! {
-    pub static ref PARSING_RULES: HashMap<(u8, u8), u8> = {
-        let mut rules = HashMap::<(u8, u8), u8>::new();
+    pub static ref PARSING_RULES: HashMap<(NonTerminal, Terminal), u16> = {
+        let mut rules = HashMap::<(NonTerminal, Terminal), u16>::new();
 
         // <scala>
         for tkn in FIRST_SCALA.iter() {
-            rules.insert((42, tkn.to_owned()), 1);
+            rules.insert((NonTerminal::SCALA, *tkn), 1);
         }
 
         // <packages>
-        rules.insert((43, 3), 2);
+        rules.insert((NonTerminal::PACKAGES, Package), 2);
         for tkn in FOLLOW_PACKAGES.iter() {
-            rules.insert((43, tkn.to_owned()), 3);
+            rules.insert((NonTerminal::PACKAGES, *tkn), 3);
         }
 
         // <imports>
-        rules.insert((44, 4), 4);
+        rules.insert((NonTerminal::IMPORTS, Import), 4);
         for tkn in FOLLOW_IMPORTS.iter() {
-            rules.insert((44, tkn.to_owned()), 5);
+            rules.insert((NonTerminal::IMPORTS, *tkn), 5);
         }
 
         // <scala-body>
         for tkn in FIRST_MODIFIER.iter() {
-            rules.insert((45, tkn.to_owned()), 6);
+            rules.insert((NonTerminal::SCALA_BODY, *tkn), 6);
         }
-        rules.insert((45, EPSILON_CODE), 7);
+        rules.insert((NonTerminal::SCALA_BODY, EndOfInput), 7);
 
         // <subbody>
         for tkn in FIRST_MODIFIER.iter() {
-            rules.insert((46, tkn.to_owned()), 8);
+            rules.insert((NonTerminal::SUBBODY, *tkn), 8);
         }
 
         // <modifier>
-        rules.insert((47, 5), 9);
-        rules.insert((47, 6), 10);
-        rules.insert((47, 7), 11);
-        rules.insert((47, 8), 12);
-        rules.insert((47, 9), 13);
+        rules.insert((NonTerminal::MODIFIER, Abstract), 9);
+        rules.insert((NonTerminal::MODIFIER, Final), 10);
+        rules.insert((NonTerminal::MODIFIER, Sealed), 11);
+        rules.insert((NonTerminal::MODIFIER, Private), 12);
+        rules.insert((NonTerminal::MODIFIER, Protected), 13);
 
         // <subbody-tail>
-        rules.insert((48, 10), 14);
-        rules.insert((48, 11), 14);
+        rules.insert((NonTerminal::SUBBODY_TAIL, Class), 14);
+        rules.insert((NonTerminal::SUBBODY_TAIL, Object), 14);
 
         // <tail-type>
-        rules.insert((49, 10), 15);
-        rules.insert((49, 11), 16);
+        rules.insert((NonTerminal::TAIL_TYPE, Class), 15);
+        rules.insert((NonTerminal::TAIL_TYPE, Object), 16);
 
         // <block>
-        rules.insert((50, 32), 17);
+        rules.insert((NonTerminal::BLOCK, LeftBrace), 17);
 
         // <stmts>
         for tkn in FIRST_STATEMENT.iter() {
-            rules.insert((51, tkn.to_owned()), 18);
+            rules.insert((NonTerminal::STMTS, *tkn), 18);
         }
-        rules.insert((51, 33), 19);
+        rules.insert((NonTerminal::STMTS, RightBrace), 19);
 
         // <stmt>
-        rules.insert((52, 12), 20);
-        rules.insert((52, 13), 20);
-        rules.insert((52, 1), 21);
-        rules.insert((52, 15), 22);
-        rules.insert((52, 17), 23);
-        rules.insert((52, 18), 24);
-        rules.insert((52, 20), 25);
-        rules.insert((52, 21), 26);
-        rules.insert((52, 22), 27);
-        rules.insert((52, 32), 28);
+        rules.insert((NonTerminal::STMT, Val), 20);
+        rules.insert((NonTerminal::STMT, Def), 20);
+        rules.insert((NonTerminal::STMT, Id), 21);
+        rules.insert((NonTerminal::STMT, If), 22);
+        rules.insert((NonTerminal::STMT, While), 23);
+        rules.insert((NonTerminal::STMT, Case), 24);
+        rules.insert((NonTerminal::STMT, In), 25);
+        rules.insert((NonTerminal::STMT, Print), 26);
+        rules.insert((NonTerminal::STMT, Return), 27);
+        rules.insert((NonTerminal::STMT, LeftBrace), 28);
 
         // <dcl>
-        rules.insert((53, 12), 29);
-        rules.insert((53, 13), 30);
+        rules.insert((NonTerminal::DCL, Val), 29);
+        rules.insert((NonTerminal::DCL, Def), 30);
 
         // <dcl-tail>
-        rules.insert((54, 1), 31);
+        rules.insert((NonTerminal::DCL_TAIL, Id), 31);
 
         // <ids>
-        rules.insert((55, 1), 32);
+        rules.insert((NonTerminal::IDS, Id), 32);
 
         // <more-ids>
-        rules.insert((56, 37), 33);
-        rules.insert((56, 36), 34);
-        rules.insert((56, 35), 34);
+        rules.insert((NonTerminal::MORE_IDS, Comma), 33);
+        rules.insert((NonTerminal::MORE_IDS, Colon), 34);
+        rules.insert((NonTerminal::MORE_IDS, RightParen), 34);
 
         // <type>
-        rules.insert((57, 28), 35);
-        rules.insert((57, 29), 36);
-        rules.insert((57, 30), 37);
+        rules.insert((NonTerminal::TYPE, Int), 35);
+        rules.insert((NonTerminal::TYPE, Real), 36);
+        rules.insert((NonTerminal::TYPE, Bool), 37);
 
         // <asmt>
-        rules.insert((58, 1), 38);
+        rules.insert((NonTerminal::ASMT, Id), 38);
 
         // <if>
-        rules.insert((59, 15), 39);
+        rules.insert((NonTerminal::IF, If), 39);
 
         // <while>
-        rules.insert((60, 17), 40);
+        rules.insert((NonTerminal::WHILE, While), 40);
 
         // <case>
-        rules.insert((61, 18), 41);
+        rules.insert((NonTerminal::CASE, Case), 41);
 
         // <in>
-        rules.insert((62, 20), 42);
+        rules.insert((NonTerminal::IN, In), 42);
 
         // <out>
-        rules.insert((63, 21), 43);
+        rules.insert((NonTerminal::OUT, Print), 43);
 
         // <return>
-        rules.insert((64, 22), 44);
+        rules.insert((NonTerminal::RETURN, Return), 44);
 
         // <expr>
-        rules.insert((65, 1), 45);
-        rules.insert((65, 2), 45);
-        rules.insert((65, 34), 45);
-        rules.insert((65, 23), 46);
-        rules.insert((65, 24), 46);
-        rules.insert((65, 25), 46);
-        rules.insert((65, 41), 46);
+        rules.insert((NonTerminal::EXPR, Id), 45);
+        rules.insert((NonTerminal::EXPR, Const), 45);
+        rules.insert((NonTerminal::EXPR, LeftParen), 45);
+        rules.insert((NonTerminal::EXPR, Not), 46);
+        rules.insert((NonTerminal::EXPR, True), 46);
+        rules.insert((NonTerminal::EXPR, False), 46);
+        rules.insert((NonTerminal::EXPR, At), 46);
 
         // <arith-expr>
-        rules.insert((66, 1), 47);
-        rules.insert((66, 2), 48);
-        rules.insert((66, 34), 49);
+        rules.insert((NonTerminal::ARITH_EXPR, Id), 47);
+        rules.insert((NonTerminal::ARITH_EXPR, Const), 48);
+        rules.insert((NonTerminal::ARITH_EXPR, LeftParen), 49);
 
         // <arith>
-        rules.insert((67, 39), 50);
-        rules.insert((67, 40), 51);
+        rules.insert((NonTerminal::ARITH, Plus), 50);
+        rules.insert((NonTerminal::ARITH, Star), 51);
         for tkn in FOLLOW_ARITH.iter() {
-            rules.insert((67, tkn.to_owned()), 52);
+            rules.insert((NonTerminal::ARITH, *tkn), 52);
         }
 
         // <bool-expr>
-        rules.insert((68, 23), 53);
-        rules.insert((68, 24), 54);
-        rules.insert((68, 25), 55);
-        rules.insert((68, 41), 56);
+        rules.insert((NonTerminal::BOOL_EXP, Not), 53);
+        rules.insert((NonTerminal::BOOL_EXP, True), 54);
+        rules.insert((NonTerminal::BOOL_EXP, False), 55);
+        rules.insert((NonTerminal::BOOL_EXP, At), 56);
 
         // <bool>
-        rules.insert((69, 26), 57);
-        rules.insert((69, 27), 58);
-        rules.insert((69, EPSILON_CODE), 59);
+        rules.insert((NonTerminal::BOOL, And), 57);
+        rules.insert((NonTerminal::BOOL, Or), 58);
+        rules.insert((NonTerminal::BOOL, EndOfInput), 59);
 
         rules
     };
@@ -235,28 +158,35 @@ lazy_static! {
 #[cfg(test)]
 mod test_parsing_rules {
     use crate::rules::PARSING_RULES;
+    use crate::symbol::{NonTerminal, Terminal};
 
     #[test]
     fn test_rule_one_contains_first_scala() {
-        assert!(PARSING_RULES.get(&(42, 5)).is_some());
+        assert!(PARSING_RULES
+            .get(&(NonTerminal::SCALA, Terminal::Abstract))
+            .is_some());
     }
 
     #[test]
     fn test_rule_three_contains_follow_packages() {
-        assert!(PARSING_RULES.get(&(43, 4)).is_some());
+        assert!(PARSING_RULES
+            .get(&(NonTerminal::PACKAGES, Terminal::Import))
+            .is_some());
     }
 
     #[test]
     fn test_invalid_key_returns_none() {
-        assert!(PARSING_RULES.get(&(69, 69)).is_none());
+        assert!(PARSING_RULES
+            .get(&(NonTerminal::BOOL, Terminal::Bool))
+            .is_none());
     }
 }
 
 // This is the static HashMap that we use to store the expansion rules.
-// The key is the id (u8) of the rule, and the value is the list of tokens to add back to the stack.
+// The key is the id of the rule, and the value is the list of symbols to add back to the stack.
 lazy_static! {
-    pub static ref EXPANSION_RULES: HashMap<u8, Vec<u8>> = {
-        let mut rules = HashMap::new();
+    pub static ref EXPANSION_RULES: HashMap<u16, Vec<Symbol>> = {
+        let mut rules = HashMap::<u16, Vec<Symbol>>::new();
 
         /*
         We are going to put them in the order of the rules for the sake of readability. When we put these symbols into the stack, however, it needs to be done in reverse order. See below on how to do that:
@@ -269,86 +199,206 @@ lazy_static! {
         */
 
         // Insert our rules, based on page 3 of the spec sheet and my markups in Notability.
-        rules.insert(1, vec![43, 44, 45]);
-        rules.insert(2, vec![3, 1, 31, 43]);
+        // T and N are short for Symbol::Terminal and Symbol::NonTerminal.
+        rules.insert(
+            1,
+            vec![
+                N(NonTerminal::PACKAGES),
+                N(NonTerminal::IMPORTS),
+                N(NonTerminal::SCALA_BODY),
+            ],
+        );
+        rules.insert(2, vec![T(Package), T(Id), T(Semicolon), N(NonTerminal::PACKAGES)]);
         rules.insert(3, vec![]); // this is an epsilon rule
-        rules.insert(4, vec![4, 1, 31, 44]);
+        rules.insert(4, vec![T(Import), T(Id), T(Semicolon), N(NonTerminal::IMPORTS)]);
         rules.insert(5, vec![]);
-        rules.insert(6, vec![46, 45]);
+        rules.insert(6, vec![N(NonTerminal::SUBBODY), N(NonTerminal::SCALA_BODY)]);
         rules.insert(7, vec![]);
-        rules.insert(8, vec![47, 48]);
-        rules.insert(9, vec![5]);
-        rules.insert(10, vec![6]);
-        rules.insert(11, vec![7]);
-        rules.insert(12, vec![8]);
-        rules.insert(13, vec![9]);
-        rules.insert(14, vec![49, 50]);
-        rules.insert(15, vec![10]);
-        rules.insert(16, vec![11]);
-        rules.insert(17, vec![32, 51, 33]);
-        rules.insert(18, vec![52, 31, 51]);
+        rules.insert(8, vec![N(NonTerminal::MODIFIER), N(NonTerminal::SUBBODY_TAIL)]);
+        rules.insert(9, vec![T(Abstract)]);
+        rules.insert(10, vec![T(Final)]);
+        rules.insert(11, vec![T(Sealed)]);
+        rules.insert(12, vec![T(Private)]);
+        rules.insert(13, vec![T(Protected)]);
+        rules.insert(14, vec![N(NonTerminal::TAIL_TYPE), N(NonTerminal::BLOCK)]);
+        rules.insert(15, vec![T(Class)]);
+        rules.insert(16, vec![T(Object)]);
+        rules.insert(17, vec![T(LeftBrace), N(NonTerminal::STMTS), T(RightBrace)]);
+        rules.insert(18, vec![N(NonTerminal::STMT), T(Semicolon), N(NonTerminal::STMTS)]);
         rules.insert(19, vec![]);
-        rules.insert(20, vec![53]);
-        rules.insert(21, vec![58]);
-        rules.insert(22, vec![59]);
-        rules.insert(23, vec![60]);
-        rules.insert(24, vec![61]);
-        rules.insert(25, vec![62]);
-        rules.insert(26, vec![63]);
-        rules.insert(27, vec![64]);
-        rules.insert(28, vec![50]);
-        rules.insert(29, vec![12, 54]);
-        rules.insert(30, vec![13, 1, 34, 55, 35, 50]);
-        rules.insert(31, vec![55, 36, 57]);
-        rules.insert(32, vec![1, 56]);
-        rules.insert(33, vec![37, 1, 56]);
+        rules.insert(20, vec![N(NonTerminal::DCL)]);
+        rules.insert(21, vec![N(NonTerminal::ASMT)]);
+        rules.insert(22, vec![N(NonTerminal::IF)]);
+        rules.insert(23, vec![N(NonTerminal::WHILE)]);
+        rules.insert(24, vec![N(NonTerminal::CASE)]);
+        rules.insert(25, vec![N(NonTerminal::IN)]);
+        rules.insert(26, vec![N(NonTerminal::OUT)]);
+        rules.insert(27, vec![N(NonTerminal::RETURN)]);
+        rules.insert(28, vec![N(NonTerminal::BLOCK)]);
+        rules.insert(29, vec![T(Val), N(NonTerminal::DCL_TAIL)]);
+        rules.insert(
+            30,
+            vec![
+                T(Def),
+                T(Id),
+                T(LeftParen),
+                N(NonTerminal::IDS),
+                T(RightParen),
+                N(NonTerminal::BLOCK),
+            ],
+        );
+        rules.insert(31, vec![N(NonTerminal::IDS), T(Colon), N(NonTerminal::TYPE)]);
+        rules.insert(32, vec![T(Id), N(NonTerminal::MORE_IDS)]);
+        rules.insert(33, vec![T(Comma), T(Id), N(NonTerminal::MORE_IDS)]);
         rules.insert(34, vec![]);
-        rules.insert(35, vec![28]);
-        rules.insert(36, vec![29]);
-        rules.insert(37, vec![30]);
-        rules.insert(38, vec![1, 14, 65]);
-        rules.insert(39, vec![15, 34, 65, 35, 52, 31, 16, 52]);
-        rules.insert(40, vec![17, 34, 65, 35, 52]);
-        rules.insert(41, vec![18, 1, 38, 65, 19, 52]);
-        rules.insert(42, vec![20, 34, 55, 35]);
-        rules.insert(43, vec![21, 34, 55, 35]);
-        rules.insert(44, vec![22, 34, 65, 35]);
-        rules.insert(45, vec![66]);
-        rules.insert(46, vec![68]);
-        rules.insert(47, vec![1, 67]);
-        rules.insert(48, vec![2, 67]);
-        rules.insert(49, vec![34, 66, 35, 67]);
-        rules.insert(50, vec![39, 66]);
-        rules.insert(51, vec![40, 66]);
+        rules.insert(35, vec![T(Int)]);
+        rules.insert(36, vec![T(Real)]);
+        rules.insert(37, vec![T(Bool)]);
+        rules.insert(38, vec![T(Id), T(Assign), N(NonTerminal::EXPR)]);
+        rules.insert(
+            39,
+            vec![
+                T(If),
+                T(LeftParen),
+                N(NonTerminal::EXPR),
+                T(RightParen),
+                N(NonTerminal::STMT),
+                T(Semicolon),
+                T(Else),
+                N(NonTerminal::STMT),
+            ],
+        );
+        rules.insert(
+            40,
+            vec![
+                T(While),
+                T(LeftParen),
+                N(NonTerminal::EXPR),
+                T(RightParen),
+                N(NonTerminal::STMT),
+            ],
+        );
+        rules.insert(
+            41,
+            vec![
+                T(Case),
+                T(Id),
+                T(Equals),
+                N(NonTerminal::EXPR),
+                T(Arrow),
+                N(NonTerminal::STMT),
+            ],
+        );
+        rules.insert(42, vec![T(In), T(LeftParen), N(NonTerminal::IDS), T(RightParen)]);
+        rules.insert(43, vec![T(Print), T(LeftParen), N(NonTerminal::IDS), T(RightParen)]);
+        rules.insert(44, vec![T(Return), T(LeftParen), N(NonTerminal::EXPR), T(RightParen)]);
+        rules.insert(45, vec![N(NonTerminal::ARITH_EXPR)]);
+        rules.insert(46, vec![N(NonTerminal::BOOL_EXP)]);
+        rules.insert(47, vec![T(Id), N(NonTerminal::ARITH)]);
+        rules.insert(48, vec![T(Const), N(NonTerminal::ARITH)]);
+        rules.insert(
+            49,
+            vec![
+                T(LeftParen),
+                N(NonTerminal::ARITH_EXPR),
+                T(RightParen),
+                N(NonTerminal::ARITH),
+            ],
+        );
+        rules.insert(50, vec![T(Plus), N(NonTerminal::ARITH_EXPR)]);
+        rules.insert(51, vec![T(Star), N(NonTerminal::ARITH_EXPR)]);
         rules.insert(52, vec![]);
-        rules.insert(53, vec![23, 34, 68, 35, 69]);
-        rules.insert(54, vec![24, 69]);
-        rules.insert(55, vec![25, 69]);
-        rules.insert(56, vec![41, 66, 66]);
-        rules.insert(57, vec![26, 68]);
-        rules.insert(58, vec![27, 68]);
+        rules.insert(
+            53,
+            vec![
+                T(Not),
+                T(LeftParen),
+                N(NonTerminal::BOOL_EXP),
+                T(RightParen),
+                N(NonTerminal::BOOL),
+            ],
+        );
+        rules.insert(54, vec![T(True), N(NonTerminal::BOOL)]);
+        rules.insert(55, vec![T(False), N(NonTerminal::BOOL)]);
+        rules.insert(56, vec![T(At), N(NonTerminal::ARITH_EXPR), N(NonTerminal::ARITH_EXPR)]);
+        rules.insert(57, vec![T(And), N(NonTerminal::BOOL_EXP)]);
+        rules.insert(58, vec![T(Or), N(NonTerminal::BOOL_EXP)]);
         rules.insert(59, vec![]); // Rule 59 is indeed an epsilon rule
 
         rules
     };
 }
 
+// The nonterminal that each rule expands, keyed by the id of the rule. Together with EXPANSION_RULES, this gives the full productions of the grammar.
+lazy_static! {
+    pub static ref RULE_HEADS: HashMap<u16, NonTerminal> = {
+        let mut heads = HashMap::new();
+
+        let rules_by_head: [(NonTerminal, &[u16]); 28] = [
+            (NonTerminal::SCALA, &[1]),
+            (NonTerminal::PACKAGES, &[2, 3]),
+            (NonTerminal::IMPORTS, &[4, 5]),
+            (NonTerminal::SCALA_BODY, &[6, 7]),
+            (NonTerminal::SUBBODY, &[8]),
+            (NonTerminal::MODIFIER, &[9, 10, 11, 12, 13]),
+            (NonTerminal::SUBBODY_TAIL, &[14]),
+            (NonTerminal::TAIL_TYPE, &[15, 16]),
+            (NonTerminal::BLOCK, &[17]),
+            (NonTerminal::STMTS, &[18, 19]),
+            (NonTerminal::STMT, &[20, 21, 22, 23, 24, 25, 26, 27, 28]),
+            (NonTerminal::DCL, &[29, 30]),
+            (NonTerminal::DCL_TAIL, &[31]),
+            (NonTerminal::IDS, &[32]),
+            (NonTerminal::MORE_IDS, &[33, 34]),
+            (NonTerminal::TYPE, &[35, 36, 37]),
+            (NonTerminal::ASMT, &[38]),
+            (NonTerminal::IF, &[39]),
+            (NonTerminal::WHILE, &[40]),
+            (NonTerminal::CASE, &[41]),
+            (NonTerminal::IN, &[42]),
+            (NonTerminal::OUT, &[43]),
+            (NonTerminal::RETURN, &[44]),
+            (NonTerminal::EXPR, &[45, 46]),
+            (NonTerminal::ARITH_EXPR, &[47, 48, 49]),
+            (NonTerminal::ARITH, &[50, 51, 52]),
+            (NonTerminal::BOOL_EXP, &[53, 54, 55, 56]),
+            (NonTerminal::BOOL, &[57, 58, 59]),
+        ];
+        for (head, rules) in rules_by_head.iter() {
+            for rule in rules.iter() {
+                heads.insert(*rule, *head);
+            }
+        }
+
+        heads
+    };
+}
+
 #[cfg(test)]
 mod test_rules_static_hashmap {
-    use crate::rules::EXPANSION_RULES;
+    use crate::rules::{EXPANSION_RULES, RULE_HEADS};
+    use crate::symbol::{NonTerminal, Symbol, Terminal};
 
     #[test]
     fn test_rule_1() {
-        let expected: u8 = 43;
+        let expected = Symbol::NonTerminal(NonTerminal::PACKAGES);
         let actual = EXPANSION_RULES.get(&1).unwrap()[0];
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_every_rule_has_a_head() {
+        for rule in EXPANSION_RULES.keys() {
+            assert!(RULE_HEADS.contains_key(rule));
+        }
+        assert_eq!(RULE_HEADS.len(), EXPANSION_RULES.len());
+    }
+
     #[test]
     fn test_rule_17() {
-        let expected: u8 = vec![32, 51, 33][0];
-        let actual: u8 = EXPANSION_RULES.get(&17).unwrap()[0];
+        let expected: Symbol = vec![Terminal::LeftBrace.into(), NonTerminal::STMTS.into()][0];
+        let actual: Symbol = EXPANSION_RULES.get(&17).unwrap()[0];
 
         assert_eq!(expected, actual);
     }
@@ -356,12 +406,12 @@ mod test_rules_static_hashmap {
 
 // These are the FIRST(1) and FOLLOW(1) sets that we will want to use when we populate our transition functions.
 lazy_static! {
-    pub static ref FIRST_SCALA: HashSet<u8> = {
-        let mut first_scala: HashSet<u8> = HashSet::<u8>::new();
+    pub static ref FIRST_SCALA: HashSet<Terminal> = {
+        let mut first_scala: HashSet<Terminal> = HashSet::<Terminal>::new();
 
-        first_scala.insert(3);
-        first_scala.insert(4);
-        first_scala.insert(EPSILON_CODE); // Inserting the epsilon rule.
+        first_scala.insert(Package);
+        first_scala.insert(Import);
+        first_scala.insert(EndOfInput); // Inserting the epsilon rule.
         // Also inserting the FIRST(1) of <modifier>
         for tkn in FIRST_MODIFIER.iter() {
             first_scala.insert(tkn.to_owned());
@@ -370,23 +420,23 @@ lazy_static! {
         first_scala
     };
 
-    pub static ref FIRST_MODIFIER: HashSet<u8> = {
-        let mut first_modifier: HashSet<u8> = HashSet::<u8>::new();
+    pub static ref FIRST_MODIFIER: HashSet<Terminal> = {
+        let mut first_modifier: HashSet<Terminal> = HashSet::<Terminal>::new();
 
-        first_modifier.insert(5);
-        first_modifier.insert(6);
-        first_modifier.insert(7);
-        first_modifier.insert(8);
-        first_modifier.insert(9);
+        first_modifier.insert(Abstract);
+        first_modifier.insert(Final);
+        first_modifier.insert(Sealed);
+        first_modifier.insert(Private);
+        first_modifier.insert(Protected);
 
         first_modifier
     };
 
-    pub static ref FOLLOW_PACKAGES: HashSet<u8> = {
-        let mut follow_packages = HashSet::<u8>::new();
+    pub static ref FOLLOW_PACKAGES: HashSet<Terminal> = {
+        let mut follow_packages = HashSet::<Terminal>::new();
 
-        follow_packages.insert(4);
-        follow_packages.insert(EPSILON_CODE);
+        follow_packages.insert(Import);
+        follow_packages.insert(EndOfInput);
         for tkn in FIRST_MODIFIER.iter() {
             follow_packages.insert(tkn.to_owned());
         }
@@ -394,10 +444,10 @@ lazy_static! {
         follow_packages
     };
 
-    pub static ref FOLLOW_IMPORTS: HashSet<u8> = {
-        let mut follow_imports = HashSet::<u8>::new();
+    pub static ref FOLLOW_IMPORTS: HashSet<Terminal> = {
+        let mut follow_imports = HashSet::<Terminal>::new();
 
-        follow_imports.insert(EPSILON_CODE);
+        follow_imports.insert(EndOfInput);
         for tkn in FIRST_MODIFIER.iter() {
             follow_imports.insert(tkn.to_owned());
         }
@@ -405,46 +455,118 @@ lazy_static! {
         follow_imports
     };
 
-    pub static ref FIRST_STATEMENT: HashSet<u8> = {
-        let mut first_statement = HashSet::<u8>::new();
+    pub static ref FIRST_STATEMENT: HashSet<Terminal> = {
+        let mut first_statement = HashSet::<Terminal>::new();
 
-        first_statement.insert(12);
-        first_statement.insert(13);
-        first_statement.insert(1);
-        first_statement.insert(15);
-        first_statement.insert(17);
-        first_statement.insert(18);
-        first_statement.insert(20);
-        first_statement.insert(21);
-        first_statement.insert(22);
-        first_statement.insert(32);
+        first_statement.insert(Val);
+        first_statement.insert(Def);
+        first_statement.insert(Id);
+        first_statement.insert(If);
+        first_statement.insert(While);
+        first_statement.insert(Case);
+        first_statement.insert(In);
+        first_statement.insert(Print);
+        first_statement.insert(Return);
+        first_statement.insert(LeftBrace);
 
         first_statement
     };
 
-    pub static ref FOLLOW_ARITH: HashSet<u8> = {
-        let mut follow_arith = HashSet::<u8>::new();
+    pub static ref FOLLOW_ARITH: HashSet<Terminal> = {
+        let mut follow_arith = HashSet::<Terminal>::new();
 
-        follow_arith.insert(31);
-        follow_arith.insert(35);
-        follow_arith.insert(19);
-        follow_arith.insert(1);
-        follow_arith.insert(2);
-        follow_arith.insert(34);
+        follow_arith.insert(Semicolon);
+        follow_arith.insert(RightParen);
+        follow_arith.insert(Arrow);
+        follow_arith.insert(Id);
+        follow_arith.insert(Const);
+        follow_arith.insert(LeftParen);
 
         follow_arith
     };
 }
 
+// The FOLLOW(1) set of every nonterminal. EndOfInput stands in for the end of the input, as it does above.
+// The parser uses these to synchronize after a syntax error: a nonterminal that cannot be expanded is abandoned as soon as the lookahead is something that may follow it.
+lazy_static! {
+    pub static ref FOLLOW_SETS: HashMap<NonTerminal, HashSet<Terminal>> = {
+        let mut follow_sets = HashMap::<NonTerminal, HashSet<Terminal>>::new();
+
+        // Everything that may follow a class or object body.
+        let mut follow_subbody: HashSet<Terminal> = FIRST_MODIFIER.clone();
+        follow_subbody.insert(EndOfInput);
+
+        // Everything that may follow an expression.
+        let follow_expr: HashSet<Terminal> = HashSet::from([Semicolon, RightParen, Arrow]);
+
+        // Everything that may follow a statement.
+        let follow_stmt: HashSet<Terminal> = HashSet::from([Semicolon]);
+
+        // <scala>
+        follow_sets.insert(NonTerminal::SCALA, HashSet::from([EndOfInput]));
+        // <packages>
+        follow_sets.insert(NonTerminal::PACKAGES, FOLLOW_PACKAGES.clone());
+        // <imports>
+        follow_sets.insert(NonTerminal::IMPORTS, FOLLOW_IMPORTS.clone());
+        // <scala-body>
+        follow_sets.insert(NonTerminal::SCALA_BODY, HashSet::from([EndOfInput]));
+        // <subbody>
+        follow_sets.insert(NonTerminal::SUBBODY, follow_subbody.clone());
+        // <modifier>
+        follow_sets.insert(NonTerminal::MODIFIER, HashSet::from([Class, Object]));
+        // <subbody-tail>
+        follow_sets.insert(NonTerminal::SUBBODY_TAIL, follow_subbody.clone());
+        // <tail-type>
+        follow_sets.insert(NonTerminal::TAIL_TYPE, HashSet::from([LeftBrace]));
+        // <block>, which may end a body, a statement or a def.
+        follow_sets.insert(
+            NonTerminal::BLOCK,
+            follow_subbody.union(&follow_stmt).copied().collect(),
+        );
+        // <stmts>
+        follow_sets.insert(NonTerminal::STMTS, HashSet::from([RightBrace]));
+        // <stmt>, <dcl>, <dcl-tail>, <type>, <asmt>, <if>, <while>, <case>, <in>, <out> and <return> all end a statement.
+        for nonterminal in [
+            NonTerminal::STMT,
+            NonTerminal::DCL,
+            NonTerminal::DCL_TAIL,
+            NonTerminal::TYPE,
+            NonTerminal::ASMT,
+            NonTerminal::IF,
+            NonTerminal::WHILE,
+            NonTerminal::CASE,
+            NonTerminal::IN,
+            NonTerminal::OUT,
+            NonTerminal::RETURN,
+        ] {
+            follow_sets.insert(nonterminal, follow_stmt.clone());
+        }
+        // <ids> and <more-ids>
+        follow_sets.insert(NonTerminal::IDS, HashSet::from([Colon, RightParen]));
+        follow_sets.insert(NonTerminal::MORE_IDS, HashSet::from([Colon, RightParen]));
+        // <expr>
+        follow_sets.insert(NonTerminal::EXPR, follow_expr.clone());
+        // <arith-expr> and <arith>
+        follow_sets.insert(NonTerminal::ARITH_EXPR, FOLLOW_ARITH.clone());
+        follow_sets.insert(NonTerminal::ARITH, FOLLOW_ARITH.clone());
+        // <bool-exp> and <bool>
+        follow_sets.insert(NonTerminal::BOOL_EXP, follow_expr.clone());
+        follow_sets.insert(NonTerminal::BOOL, follow_expr);
+
+        follow_sets
+    };
+}
+
 // These are unit tests for our transition function rules.
 #[cfg(test)]
 mod test_transition_rules {
-    use crate::rules::{EPSILON_CODE, FIRST_MODIFIER, FIRST_SCALA, FOLLOW_PACKAGES};
+    use crate::rules::{FIRST_MODIFIER, FIRST_SCALA, FOLLOW_PACKAGES, FOLLOW_SETS};
+    use crate::symbol::{NonTerminal, Terminal};
 
     #[test]
     fn test_first_scala_contains_package() {
         let expected: bool = true;
-        let package_code: u8 = 3;
+        let package_code = Terminal::Package;
         let actual: bool = FIRST_SCALA.contains(&package_code);
 
         assert_eq!(expected, actual);
@@ -453,7 +575,7 @@ mod test_transition_rules {
     #[test]
     fn test_first_modifier_contains_sealed() {
         let expected: bool = true;
-        let sealed_code: u8 = 7;
+        let sealed_code = Terminal::Sealed;
         let actual: bool = FIRST_MODIFIER.contains(&sealed_code);
 
         assert_eq!(expected, actual);
@@ -462,16 +584,32 @@ mod test_transition_rules {
     #[test]
     fn test_first_scala_contains_first_modifier() {
         let expected: bool = true;
-        let sealed_code = 7;
+        let sealed_code = Terminal::Sealed;
         let actual: bool = FIRST_SCALA.contains(&sealed_code);
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_every_nonterminal_has_a_follow_set() {
+        for code in 42..=69 {
+            let nonterminal = NonTerminal::new(code).unwrap();
+            assert!(FOLLOW_SETS.contains_key(&nonterminal));
+        }
+    }
+
+    #[test]
+    fn test_follow_block_contains_semicolon_and_modifiers() {
+        let follow_block = FOLLOW_SETS.get(&NonTerminal::BLOCK).unwrap();
+
+        assert!(follow_block.contains(&Terminal::Semicolon));
+        assert!(follow_block.is_superset(&FIRST_MODIFIER));
+    }
+
     #[test]
     fn test_follow_packages_contains_epsilon() {
         let expected: bool = true;
-        let actual: bool = FOLLOW_PACKAGES.contains(&EPSILON_CODE);
+        let actual: bool = FOLLOW_PACKAGES.contains(&Terminal::EndOfInput);
 
         assert_eq!(expected, actual);
     }
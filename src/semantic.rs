@@ -0,0 +1,519 @@
+#![warn(clippy::all)]
+
+// The semantic analysis of an accepted program: checking that every identifier is declared before it is used, and declared only once, and that the types of its expressions fit together.
+// Declarations live in nested scopes. Each class or object body has one, as does each block, and each `def` has one for its parameters with its block inside it.
+// The statement of an `if`, `while` or `case` gets a scope of its own too, so that a declaration there does not leak out into the statements after it.
+// A declaration with the same name as one in an enclosing scope is allowed, but it hides the outer one, so it gets a warning.
+//
+// The types are the ones that a `val` declaration gives: int, real and bool. A constant with a period in it is a real, and any other is an int.
+// `+` and `*` take two numbers, and give a real if either of them is a real. `@` takes two numbers and gives a bool, as do `not`, `and`, `or`, `true` and `false`.
+// An int can be assigned to a real variable, but not the other way around, and the condition of an `if` or a `while` has to be a bool.
+// Parameters and functions have no declared type, so anything that they are part of is not checked.
+//
+// Every scope and declaration goes into the scoped symbol table of the bookkeeper, which is where names are looked up. Each place that a name was found is marked there as where it was defined or used, and which declaration it stands for.
+// Packages are declared in the program's scope, but they are not variables, so they cannot be used in a statement and a variable with the same name does not shadow them.
+
+use crate::ast::*;
+use crate::bookkeeper::{Bookkeeper, Role, SymbolKind, PROGRAM_SCOPE};
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, ErrorType};
+use crate::log::{log, Level};
+
+// Analyze a program, filling in the scopes and declarations of the symbol table, and return its errors and warnings in the order that they appear in the source.
+pub fn analyze(program: &Program, file: &str, symtab: &mut Bookkeeper) -> Vec<Diagnostic> {
+    let mut analyzer = Analyzer {
+        file,
+        symtab,
+        scope: PROGRAM_SCOPE,
+        diagnostics: Vec::new(),
+    };
+
+    for package in &program.packages {
+        analyzer.declare(package, SymbolKind::Package, None);
+    }
+    for (i, body) in program.bodies.iter().enumerate() {
+        let name = match body.kind {
+            BodyKind::Class => format!("class {}", i + 1),
+            BodyKind::Object => format!("object {}", i + 1),
+        };
+        analyzer.scoped(&name, |analyzer| analyzer.block(&body.block));
+    }
+
+    // A statement is checked from the inside out, so its problems are not always found in order.
+    let mut diagnostics = analyzer.diagnostics;
+    diagnostics.sort_by_key(|d| (d.line_number(), d.column()));
+
+    diagnostics
+}
+
+// Walks the tree, keeping track of the innermost scope that is open.
+struct Analyzer<'a> {
+    file: &'a str,
+    symtab: &'a mut Bookkeeper,
+    scope: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Analyzer<'a> {
+    // Run `f` in a new scope inside the current one, which is closed again afterwards.
+    fn scoped(&mut self, name: &str, f: impl FnOnce(&mut Self)) {
+        let outer = self.scope;
+        self.scope = self.symtab.open_scope(outer, name);
+        f(self);
+        self.scope = outer;
+    }
+
+    fn report(&mut self, error_type: ErrorType, id: &Identifier) {
+        let error = Error {
+            error_type,
+            token: id.name.clone(),
+            line_number: id.line_number,
+            column: id.column,
+            span: id.span,
+        };
+
+        self.diagnostics
+            .push(Diagnostic::new(self.file, error, Vec::new()));
+    }
+
+    // Declare a name in the current scope.
+    fn declare(&mut self, id: &Identifier, kind: SymbolKind, var_type: Option<Type>) {
+        // A name that was already declared in an outer scope is hidden by this one.
+        let shadows = match self.symtab.scope(self.scope).parent() {
+            Some(parent) => self.variable(parent, &id.name).is_some(),
+            None => false,
+        };
+
+        let declared = self
+            .symtab
+            .declare(self.scope, &id.name, kind, var_type, id.line_number);
+        // Either way, this is where the name is defined, even if it was defined here before.
+        let (Ok(index) | Err(index)) = declared;
+        self.symtab
+            .mark(id.line_number, id.column, Role::Definition, index);
+        // The first declaration is the one that counts.
+        if declared.is_err() {
+            self.report(ErrorType::DuplicateDeclaration, id);
+            return;
+        }
+        log!(
+            Level::Debug,
+            "declared {} {} in {}",
+            kind,
+            id.name,
+            self.symtab.scope_path(self.scope)
+        );
+        if shadows {
+            self.report(ErrorType::ShadowedDeclaration, id);
+        }
+    }
+
+    // The number of the declaration of a variable, function or parameter that a name in a scope stands for.
+    fn variable(&self, scope: usize, name: &str) -> Option<usize> {
+        self.symtab
+            .lookup_index(scope, name)
+            .filter(|index| self.symtab.declaration(*index).kind() != SymbolKind::Package)
+    }
+
+    // Check that a name that is used has been declared, in the current scope or one around it, and return its type.
+    fn reference(&mut self, id: &Identifier) -> Option<Type> {
+        match self.variable(self.scope, &id.name) {
+            Some(index) => {
+                self.symtab
+                    .mark(id.line_number, id.column, Role::Use, index);
+                self.symtab.declaration(index).var_type()
+            }
+            None => {
+                self.report(ErrorType::UndeclaredIdentifier, id);
+                None
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        for statement in &block.statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Val { names, var_type } => {
+                for name in names {
+                    self.declare(name, SymbolKind::Variable, Some(*var_type));
+                }
+            }
+            Statement::Def { name, params, body } => {
+                // The name is declared first, so that the function can call itself.
+                self.declare(name, SymbolKind::Function, None);
+                self.scoped(&format!("def {}", name.name), |analyzer| {
+                    for param in params {
+                        analyzer.declare(param, SymbolKind::Parameter, None);
+                    }
+                    analyzer.scoped("block", |analyzer| analyzer.block(body));
+                });
+            }
+            Statement::Assign { target, value } => {
+                let target_type = self.reference(target);
+                let value_type = self.expr(value);
+                if let (Some(target_type), Some(value_type)) = (target_type, value_type) {
+                    if !assignable(target_type, value_type) {
+                        let mismatch = ErrorType::AssignmentMismatch {
+                            target: target_type,
+                            value: value_type,
+                        };
+                        self.report(mismatch, target);
+                    }
+                }
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.condition(condition);
+                self.scoped("if", |analyzer| analyzer.statement(then_branch));
+                self.scoped("else", |analyzer| analyzer.statement(else_branch));
+            }
+            Statement::While { condition, body } => {
+                self.condition(condition);
+                self.scoped("while", |analyzer| analyzer.statement(body));
+            }
+            Statement::Case { name, value, body } => {
+                self.reference(name);
+                self.expr(value);
+                self.scoped("case", |analyzer| analyzer.statement(body));
+            }
+            Statement::In { names } | Statement::Print { names } => {
+                for name in names {
+                    self.reference(name);
+                }
+            }
+            Statement::Return { value } => {
+                self.expr(value);
+            }
+            Statement::Block(block) => self.scoped("block", |analyzer| analyzer.block(block)),
+        }
+    }
+
+    // The condition of an `if` or a `while`, which has to be a bool.
+    fn condition(&mut self, condition: &Expr) {
+        if let (Expr::Arith(arith), Some(found)) = (condition, self.expr(condition)) {
+            if found != Type::Bool {
+                self.report(ErrorType::NonBooleanCondition(found), &leftmost(arith));
+            }
+        }
+    }
+
+    // The type of an expression, or None if it cannot be known.
+    fn expr(&mut self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Arith(arith) => self.arith_expr(arith),
+            Expr::Bool(boolean) => {
+                self.bool_expr(boolean);
+                Some(Type::Bool)
+            }
+        }
+    }
+
+    // The type of an arithmetic expression. It is only a bool if it is nothing but a bool variable.
+    fn arith_expr(&mut self, arith: &ArithExpr) -> Option<Type> {
+        match arith {
+            ArithExpr::Identifier(id) => self.reference(id),
+            ArithExpr::Constant(constant) if constant.value.contains('.') => Some(Type::Real),
+            ArithExpr::Constant(_) => Some(Type::Int),
+            ArithExpr::Binary { left, right, .. } => {
+                match (self.operand(left), self.operand(right)) {
+                    (Some(Type::Int), Some(Type::Int)) => Some(Type::Int),
+                    (Some(_), Some(_)) => Some(Type::Real),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    // An operand of `+`, `*` or `@`, which has to be a number.
+    fn operand(&mut self, arith: &ArithExpr) -> Option<Type> {
+        match self.arith_expr(arith) {
+            Some(Type::Bool) => {
+                self.report(ErrorType::BooleanOperand, &leftmost(arith));
+                None
+            }
+            found => found,
+        }
+    }
+
+    fn bool_expr(&mut self, boolean: &BoolExpr) {
+        match boolean {
+            BoolExpr::Literal(_) => {}
+            BoolExpr::Not(inner) => self.bool_expr(inner),
+            BoolExpr::Compare { left, right } => {
+                self.operand(left);
+                self.operand(right);
+            }
+            BoolExpr::Binary { left, right, .. } => {
+                self.bool_expr(left);
+                self.bool_expr(right);
+            }
+        }
+    }
+}
+
+// Whether a value of one type can be assigned to a variable of another. The only conversion is from int to real.
+fn assignable(target: Type, value: Type) -> bool {
+    target == value || (target == Type::Real && value == Type::Int)
+}
+
+// The leftmost identifier or constant in an arithmetic expression, which is where a problem with the expression is pointed out.
+fn leftmost(arith: &ArithExpr) -> Identifier {
+    match arith {
+        ArithExpr::Identifier(id) => id.clone(),
+        ArithExpr::Constant(constant) => Identifier {
+            name: constant.value.clone(),
+            line_number: constant.line_number,
+            column: constant.column,
+            span: constant.span,
+        },
+        ArithExpr::Binary { left, .. } => leftmost(left),
+    }
+}
+
+#[cfg(test)]
+mod semantic_tests {
+    use crate::bookkeeper::{Bookkeeper, Occurrence};
+    use crate::parser::Parser;
+    use crate::semantic::*;
+
+    // The errors and warnings in a program, as (type, name, line).
+    fn analyze_source(src: &str) -> Vec<(ErrorType, String, usize)> {
+        let mut p = Parser::new(src.to_string(), Bookkeeper::new());
+        let program = p.parse_program().unwrap();
+
+        analyze(&program, "test.ssc", &mut Bookkeeper::new())
+            .into_iter()
+            .map(|d| {
+                (
+                    d.error_type().clone(),
+                    d.token().to_string(),
+                    d.line_number(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_declared_before_use() {
+        let src = "abstract class {
+        val a, b : int;
+        a <= b + 1;
+        print (a, b);
+        }
+        $
+        ";
+
+        assert!(analyze_source(src).is_empty());
+    }
+
+    #[test]
+    fn test_undeclared() {
+        let src = "abstract class {
+        a <= 1;
+        val a : int;
+        case a = b => in (c);
+        }
+        $
+        ";
+
+        assert_eq!(
+            analyze_source(src),
+            vec![
+                (ErrorType::UndeclaredIdentifier, "a".to_string(), 2),
+                (ErrorType::UndeclaredIdentifier, "b".to_string(), 4),
+                (ErrorType::UndeclaredIdentifier, "c".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let src = "final object {
+        val a, b : int;
+        val b : real;
+        def f (x, x) { };
+        }
+        $
+        ";
+
+        assert_eq!(
+            analyze_source(src),
+            vec![
+                (ErrorType::DuplicateDeclaration, "b".to_string(), 3),
+                (ErrorType::DuplicateDeclaration, "x".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scopes() {
+        let src = "sealed class {
+        val a : int;
+        def f (y) { val a : bool; y <= a; };
+        { val z : int; };
+        while (true) val w : int;
+        print (z, w, y, f);
+        }
+        private class { print (a); }
+        $
+        ";
+
+        assert_eq!(
+            analyze_source(src),
+            vec![
+                (ErrorType::ShadowedDeclaration, "a".to_string(), 3),
+                (ErrorType::UndeclaredIdentifier, "z".to_string(), 6),
+                (ErrorType::UndeclaredIdentifier, "w".to_string(), 6),
+                (ErrorType::UndeclaredIdentifier, "y".to_string(), 6),
+                (ErrorType::UndeclaredIdentifier, "a".to_string(), 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_types() {
+        let src = "abstract class {
+        val tt, ff : bool;
+        val x : int;
+        val r : real;
+        tt <= 47 * x;
+        r <= x + 2;
+        x <= r;
+        x <= 2.5 * x;
+        tt <= not (false) and @ x r;
+        ff <= tt;
+        if (x) print (x); else print (r);
+        while (@ tt 1) in (x);
+        x <= x + tt;
+        def f (p) { p <= tt; x <= p * 2; tt <= f; };
+        }
+        $
+        ";
+
+        assert_eq!(
+            analyze_source(src),
+            vec![
+                (
+                    ErrorType::AssignmentMismatch {
+                        target: Type::Bool,
+                        value: Type::Int
+                    },
+                    "tt".to_string(),
+                    5
+                ),
+                (
+                    ErrorType::AssignmentMismatch {
+                        target: Type::Int,
+                        value: Type::Real
+                    },
+                    "x".to_string(),
+                    7
+                ),
+                (
+                    ErrorType::AssignmentMismatch {
+                        target: Type::Int,
+                        value: Type::Real
+                    },
+                    "x".to_string(),
+                    8
+                ),
+                (
+                    ErrorType::NonBooleanCondition(Type::Int),
+                    "x".to_string(),
+                    11
+                ),
+                (ErrorType::BooleanOperand, "tt".to_string(), 12),
+                (ErrorType::BooleanOperand, "tt".to_string(), 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_condition_points_at_the_expression() {
+        let src = "final class { val a : real; while ((2.5 + a) * a) a <= 1; }\n$\n";
+        let mut p = Parser::new(src.to_string(), Bookkeeper::new());
+        let diagnostics = analyze(
+            &p.parse_program().unwrap(),
+            "test.ssc",
+            &mut Bookkeeper::new(),
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "test.ssc:1:37: Condition is real instead of bool at `2.5`"
+        );
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let src = "package a;
+        abstract class {
+        val a : int;
+        def f (a) { a <= 2; };
+        a <= a + 2;
+        print (b);
+        }
+        $
+        ";
+        let mut p = Parser::new(src.to_string(), Bookkeeper::new());
+        let program = p.parse_program().unwrap();
+        let mut symtab = p.symtab().clone();
+        analyze(&program, "test.ssc", &mut symtab);
+
+        let sites = |occurrences: &[&Occurrence]| -> Vec<(usize, usize)> {
+            occurrences
+                .iter()
+                .map(|o| (o.line_number(), o.column()))
+                .collect()
+        };
+        let references = symtab.cross_references();
+        let names: Vec<(&str, String)> = references.iter().map(|r| (r.name(), r.kind())).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("a", "package".to_string()),
+                ("a", "variable".to_string()),
+                ("f", "function".to_string()),
+                ("a", "parameter".to_string()),
+                ("2", "constant".to_string()),
+                ("b", "identifier".to_string()),
+            ]
+        );
+        assert_eq!(sites(references[1].definitions()), vec![(3, 13)]);
+        assert_eq!(sites(references[1].uses()), vec![(5, 9), (5, 14)]);
+        assert_eq!(sites(references[3].definitions()), vec![(4, 16)]);
+        assert_eq!(sites(references[3].uses()), vec![(4, 21)]);
+        assert!(references[5].definitions().is_empty());
+    }
+
+    #[test]
+    fn test_diagnostic_position() {
+        let src = "abstract class { print (nope); }\n$\n";
+        let mut p = Parser::new(src.to_string(), Bookkeeper::new());
+        let diagnostics = analyze(
+            &p.parse_program().unwrap(),
+            "test.ssc",
+            &mut Bookkeeper::new(),
+        );
+
+        assert_eq!(diagnostics[0].column(), 25);
+        assert_eq!(
+            diagnostics[0].render(src),
+            "error: Undeclared identifier `nope`
+ --> test.ssc:1:25
+  |
+1 | abstract class { print (nope); }
+  |                         ^^^^
+"
+        );
+    }
+}
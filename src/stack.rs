@@ -1,55 +1,63 @@
-// A simple implementation of a stack using Rust's Vec<u8>. I am doing this to ensure that the behaviors are as expected with a stack, since Rust wants us to use a data structure that some might consider inappropriate for this.
+// A simple implementation of a stack of grammar symbols using Rust's Vec. I am doing this to ensure that the behaviors are as expected with a stack, since Rust wants us to use a data structure that some might consider inappropriate for this.
+
+use crate::log::{log, Level};
+use crate::symbol::Symbol;
 
-use crate::DEBUG;
 pub struct Stack {
-    stack: Vec<u8>,
+    stack: Vec<Symbol>,
 }
 
 impl Stack {
     // Create a new stack
     pub fn new() -> Self {
         // This really doesn't need to be declared as mutable? Interesting.
-        let stack = vec![0];
+        let stack = vec![Symbol::Bottom];
         Stack { stack }
     }
 
     // Push to the stack.
-    pub fn push(&mut self, n: u8) {
-        if DEBUG {
-            println!("Pushing {} to the stack.", n);
-        }
-
+    pub fn push(&mut self, n: Symbol) {
         self.stack.push(n);
 
-        if DEBUG {
-            dbg!(&self.stack);
-        }
+        log!(Level::Trace, "pushed {}, giving {}", n.name(), self);
     }
 
     // Pop an element from the stack, and panic if we do so in an illegal way.
-    pub fn pop(&mut self) -> u8 {
+    pub fn pop(&mut self) -> Symbol {
         if self.is_empty() {
             panic!("Attempting to pop() from an empty stack!");
         }
         let ret = self.stack.pop().unwrap();
 
-        if DEBUG {
-            println!("Popping {} from the stack.", ret);
-            dbg!(&self.stack);
-        }
+        log!(Level::Trace, "popped {}, leaving {}", ret.name(), self);
 
         ret
     }
 
+    // Everything on the stack, from the bottom up.
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.stack
+    }
+
     // Determine whether or not we have reached the stack bottom marker.
     pub fn is_empty(&self) -> bool {
-        self.stack.len() == 1 && self.stack[0] == 0
+        self.stack.len() == 1 && self.stack[0] == Symbol::Bottom
+    }
+}
+
+// This tells the program how to println a stack in a nice way, from the bottom up.
+impl std::fmt::Display for Stack {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let names: Vec<&str> = self.stack.iter().map(|symbol| symbol.name()).collect();
+
+        write!(f, "{}", names.join(" "))
     }
 }
 
 #[cfg(test)]
 mod stack_tests {
     use crate::stack::Stack;
+    use crate::symbol::{NonTerminal, Symbol, Terminal};
 
     // Test that the stack is initialized as we expect it.
     #[test]
@@ -57,7 +65,7 @@ mod stack_tests {
         let s = Stack::new();
 
         assert_eq!(s.stack.len(), 1);
-        assert_eq!(s.stack[0], 0);
+        assert_eq!(s.stack[0], Symbol::Bottom);
     }
 
     // Test that adding elements to the stack works as expected.
@@ -65,9 +73,9 @@ mod stack_tests {
     fn test_stack_adding_elements() {
         let mut s = Stack::new();
 
-        s.push(1);
-        s.push(6);
-        s.push(69);
+        s.push(Terminal::Id.into());
+        s.push(Terminal::Final.into());
+        s.push(NonTerminal::BOOL.into());
 
         assert!(!s.is_empty());
         assert_eq!(s.stack.len(), 4);
@@ -78,7 +86,7 @@ mod stack_tests {
         let mut s = Stack::new();
 
         for ii in 1..9 {
-            s.push(ii);
+            s.push(Symbol::from_code(ii).unwrap());
         }
 
         for _ in 1..8 {
@@ -87,6 +95,6 @@ mod stack_tests {
 
         assert!(!s.is_empty());
         assert_eq!(s.stack.len(), 2);
-        assert_eq!(s.pop(), 1);
+        assert_eq!(s.pop(), Symbol::Terminal(Terminal::Id));
     }
 }
@@ -0,0 +1,465 @@
+#![warn(clippy::all)]
+
+// The symbols of the grammar: terminals (what the scanner produces), nonterminals, and the bottom of the stack.
+// Each of them still has the integer code that it has always had, for the textual output:
+// - `0` -> `z0`, the bottom of the stack
+// - `1` to `41` -> terminals
+// - `42` to `69` -> the nonterminals of the built-in grammar, with any others (from a grammar file) numbered from `70` upwards
+// - `253` -> `epsilon`, which is also the end of the input
+// - `254` -> `#`
+
+use std::collections::BTreeMap;
+
+// A terminal of Simple Scala.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(u8)]
+pub enum Terminal {
+    Id = 1,
+    Const = 2,
+    Package = 3,
+    Import = 4,
+    Abstract = 5,
+    Final = 6,
+    Sealed = 7,
+    Private = 8,
+    Protected = 9,
+    Class = 10,
+    Object = 11,
+    Val = 12,
+    Def = 13,
+    Assign = 14, // <=
+    If = 15,
+    Else = 16,
+    While = 17,
+    Case = 18,
+    Arrow = 19, // =>
+    In = 20,
+    Print = 21,
+    Return = 22,
+    Not = 23,
+    True = 24,
+    False = 25,
+    And = 26,
+    Or = 27,
+    Int = 28,
+    Real = 29,
+    Bool = 30,
+    Semicolon = 31,
+    LeftBrace = 32,
+    RightBrace = 33,
+    LeftParen = 34,
+    RightParen = 35,
+    Colon = 36,
+    Comma = 37,
+    Equals = 38,
+    Plus = 39,
+    Star = 40,
+    At = 41,
+    // The end of the input. Epsilon rules are chosen on it when nothing else fits.
+    EndOfInput = 253,
+    // A comment, which the grammar has no place for.
+    Comment = 254,
+}
+
+impl Terminal {
+    // Every terminal that can appear in the grammar, in order.
+    pub const ALL: [Terminal; 41] = [
+        Terminal::Id,
+        Terminal::Const,
+        Terminal::Package,
+        Terminal::Import,
+        Terminal::Abstract,
+        Terminal::Final,
+        Terminal::Sealed,
+        Terminal::Private,
+        Terminal::Protected,
+        Terminal::Class,
+        Terminal::Object,
+        Terminal::Val,
+        Terminal::Def,
+        Terminal::Assign,
+        Terminal::If,
+        Terminal::Else,
+        Terminal::While,
+        Terminal::Case,
+        Terminal::Arrow,
+        Terminal::In,
+        Terminal::Print,
+        Terminal::Return,
+        Terminal::Not,
+        Terminal::True,
+        Terminal::False,
+        Terminal::And,
+        Terminal::Or,
+        Terminal::Int,
+        Terminal::Real,
+        Terminal::Bool,
+        Terminal::Semicolon,
+        Terminal::LeftBrace,
+        Terminal::RightBrace,
+        Terminal::LeftParen,
+        Terminal::RightParen,
+        Terminal::Colon,
+        Terminal::Comma,
+        Terminal::Equals,
+        Terminal::Plus,
+        Terminal::Star,
+        Terminal::At,
+    ];
+
+    // The legacy integer code of the terminal.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    // The terminal with the given legacy code, if there is one.
+    pub fn from_code(code: u8) -> Option<Terminal> {
+        match code {
+            253 => Some(Terminal::EndOfInput),
+            254 => Some(Terminal::Comment),
+            code => Terminal::ALL.iter().copied().find(|t| t.code() == code),
+        }
+    }
+
+    // The terminal with the given name, as it is written in the output and in grammar files.
+    pub fn from_name(name: &str) -> Option<Terminal> {
+        Terminal::ALL.iter().copied().find(|t| t.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Terminal::Id => "[id]",
+            Terminal::Const => "[const]",
+            Terminal::Package => "package",
+            Terminal::Import => "import",
+            Terminal::Abstract => "abstract",
+            Terminal::Final => "final",
+            Terminal::Sealed => "sealed",
+            Terminal::Private => "private",
+            Terminal::Protected => "protected",
+            Terminal::Class => "class",
+            Terminal::Object => "object",
+            Terminal::Val => "val",
+            Terminal::Def => "def",
+            Terminal::Assign => "<=",
+            Terminal::If => "if",
+            Terminal::Else => "else",
+            Terminal::While => "while",
+            Terminal::Case => "case",
+            Terminal::Arrow => "=>",
+            Terminal::In => "in",
+            Terminal::Print => "print",
+            Terminal::Return => "return",
+            Terminal::Not => "not",
+            Terminal::True => "true",
+            Terminal::False => "false",
+            Terminal::And => "and",
+            Terminal::Or => "or",
+            Terminal::Int => "int",
+            Terminal::Real => "real",
+            Terminal::Bool => "bool",
+            Terminal::Semicolon => ";",
+            Terminal::LeftBrace => "{",
+            Terminal::RightBrace => "}",
+            Terminal::LeftParen => "(",
+            Terminal::RightParen => ")",
+            Terminal::Colon => ":",
+            Terminal::Comma => ",",
+            Terminal::Equals => "=",
+            Terminal::Plus => "+",
+            Terminal::Star => "*",
+            Terminal::At => "@",
+            Terminal::EndOfInput => "epsilon",
+            Terminal::Comment => "#",
+        }
+    }
+}
+
+// A nonterminal, identified by its legacy code.
+// The nonterminals of the built-in grammar are the associated constants. A grammar file may add more, with codes from 70 upwards, which is why this is not a u8.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NonTerminal(u16);
+
+impl NonTerminal {
+    pub const SCALA: NonTerminal = NonTerminal(42);
+    pub const PACKAGES: NonTerminal = NonTerminal(43);
+    pub const IMPORTS: NonTerminal = NonTerminal(44);
+    pub const SCALA_BODY: NonTerminal = NonTerminal(45);
+    pub const SUBBODY: NonTerminal = NonTerminal(46);
+    pub const MODIFIER: NonTerminal = NonTerminal(47);
+    pub const SUBBODY_TAIL: NonTerminal = NonTerminal(48);
+    pub const TAIL_TYPE: NonTerminal = NonTerminal(49);
+    pub const BLOCK: NonTerminal = NonTerminal(50);
+    pub const STMTS: NonTerminal = NonTerminal(51);
+    pub const STMT: NonTerminal = NonTerminal(52);
+    pub const DCL: NonTerminal = NonTerminal(53);
+    pub const DCL_TAIL: NonTerminal = NonTerminal(54);
+    pub const IDS: NonTerminal = NonTerminal(55);
+    pub const MORE_IDS: NonTerminal = NonTerminal(56);
+    pub const TYPE: NonTerminal = NonTerminal(57);
+    pub const ASMT: NonTerminal = NonTerminal(58);
+    pub const IF: NonTerminal = NonTerminal(59);
+    pub const WHILE: NonTerminal = NonTerminal(60);
+    pub const CASE: NonTerminal = NonTerminal(61);
+    pub const IN: NonTerminal = NonTerminal(62);
+    pub const OUT: NonTerminal = NonTerminal(63);
+    pub const RETURN: NonTerminal = NonTerminal(64);
+    pub const EXPR: NonTerminal = NonTerminal(65);
+    pub const ARITH_EXPR: NonTerminal = NonTerminal(66);
+    pub const ARITH: NonTerminal = NonTerminal(67);
+    pub const BOOL_EXP: NonTerminal = NonTerminal(68);
+    pub const BOOL: NonTerminal = NonTerminal(69);
+
+    // The first code that is free for a nonterminal that is not in the built-in grammar.
+    pub const FIRST_NEW_CODE: u16 = 70;
+
+    // The names of the nonterminals of the built-in grammar, in order of their codes.
+    const BUILTIN_NAMES: [&'static str; 28] = [
+        "<scala>",
+        "<packages>",
+        "<imports>",
+        "<scala-body>",
+        "<subbody>",
+        "<modifier>",
+        "<subbody-tail>",
+        "<tail-type>",
+        "<block>",
+        "<stmts>",
+        "<stmt>",
+        "<dcl>",
+        "<dcl-tail>",
+        "<ids>",
+        "<more-ids>",
+        "<type>",
+        "<asmt>",
+        "<if>",
+        "<while>",
+        "<case>",
+        "<in>",
+        "<out>",
+        "<return>",
+        "<expr>",
+        "<arith-expr>",
+        "<arith>",
+        "<bool-exp>",
+        "<bool>",
+    ];
+
+    // The nonterminal with the given legacy code. The codes of the bottom of the stack, the terminals and the special codes 253 to 255 are not nonterminals.
+    pub fn new(code: u16) -> Option<NonTerminal> {
+        if code < NonTerminal::SCALA.0 || (253..=255).contains(&code) {
+            return None;
+        }
+
+        Some(NonTerminal(code))
+    }
+
+    // The nonterminal of the built-in grammar with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<NonTerminal> {
+        NonTerminal::BUILTIN_NAMES
+            .iter()
+            .position(|builtin| *builtin == name)
+            .map(|index| NonTerminal(NonTerminal::SCALA.0 + index as u16))
+    }
+
+    // The legacy integer code of the nonterminal.
+    pub fn code(self) -> u16 {
+        self.0
+    }
+
+    // The name of the nonterminal, if it is one of the built-in grammar's. Those from grammar files keep their names alongside the grammar.
+    pub fn name(self) -> Option<&'static str> {
+        NonTerminal::BUILTIN_NAMES
+            .get(usize::from(self.0 - NonTerminal::SCALA.0))
+            .copied()
+    }
+}
+
+// Anything that can be on the PDA's stack.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Symbol {
+    Bottom, // z0
+    Terminal(Terminal),
+    NonTerminal(NonTerminal),
+}
+
+impl Symbol {
+    // The symbol with the given legacy code, if there is one.
+    pub fn from_code(code: u16) -> Option<Symbol> {
+        if code == 0 {
+            return Some(Symbol::Bottom);
+        }
+
+        match u8::try_from(code).ok().and_then(Terminal::from_code) {
+            Some(terminal) => Some(Symbol::Terminal(terminal)),
+            None => NonTerminal::new(code).map(Symbol::NonTerminal),
+        }
+    }
+
+    // The legacy integer code of the symbol.
+    pub fn code(self) -> u16 {
+        match self {
+            Symbol::Bottom => 0,
+            Symbol::Terminal(terminal) => u16::from(terminal.code()),
+            Symbol::NonTerminal(nonterminal) => nonterminal.code(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symbol::Bottom => "z0",
+            Symbol::Terminal(terminal) => terminal.name(),
+            Symbol::NonTerminal(nonterminal) => nonterminal.name().unwrap_or("<nonterminal>"),
+        }
+    }
+
+    // The name of the symbol, using the given names for any nonterminals that are not in the built-in grammar.
+    pub fn name_in(self, names: &BTreeMap<NonTerminal, String>) -> String {
+        match self {
+            Symbol::NonTerminal(nonterminal) if names.contains_key(&nonterminal) => {
+                names[&nonterminal].clone()
+            }
+            symbol => symbol.name().to_string(),
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Symbol::Terminal(_))
+    }
+}
+
+impl From<Terminal> for Symbol {
+    fn from(terminal: Terminal) -> Self {
+        Symbol::Terminal(terminal)
+    }
+}
+
+impl From<NonTerminal> for Symbol {
+    fn from(nonterminal: NonTerminal) -> Self {
+        Symbol::NonTerminal(nonterminal)
+    }
+}
+
+// This tells the program how to println a symbol in the legacy form, with its name and its code.
+// Example: <scala> -> `<scala> (42)`.
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name(), self.code())
+    }
+}
+
+impl std::fmt::Display for Terminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        Symbol::Terminal(*self).fmt(f)
+    }
+}
+
+impl std::fmt::Display for NonTerminal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        Symbol::NonTerminal(*self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod symbol_code_tests {
+    use crate::symbol::*;
+
+    fn is_terminal_code(code: u16) -> bool {
+        Symbol::from_code(code).is_some_and(Symbol::is_terminal)
+    }
+
+    #[test]
+    fn test_zero() {
+        assert_eq!(Symbol::from_code(0), Some(Symbol::Bottom));
+        assert!(!is_terminal_code(0));
+    }
+
+    #[test]
+    fn test_one() {
+        assert_eq!(Symbol::from_code(1), Some(Symbol::Terminal(Terminal::Id)));
+        assert!(is_terminal_code(1));
+    }
+
+    #[test]
+    fn test_forty_one() {
+        assert!(is_terminal_code(41));
+    }
+
+    #[test]
+    fn test_forty_two() {
+        assert_eq!(
+            Symbol::from_code(42),
+            Some(Symbol::NonTerminal(NonTerminal::SCALA))
+        );
+        assert!(!is_terminal_code(42));
+    }
+
+    #[test]
+    fn test_forty_three() {
+        assert!(!is_terminal_code(43));
+    }
+
+    #[test]
+    fn test_sixty_nine() {
+        assert!(!is_terminal_code(69));
+    }
+
+    // Testing a couple of edge cases.
+    #[test]
+    fn test_seventy() {
+        assert!(!is_terminal_code(70));
+        assert_eq!(NonTerminal::new(70).unwrap().name(), None);
+    }
+
+    #[test]
+    fn test_epsilon() {
+        assert_eq!(Terminal::from_code(253), Some(Terminal::EndOfInput));
+        assert!(!Terminal::ALL.contains(&Terminal::EndOfInput));
+    }
+
+    #[test]
+    fn test_error_code_is_not_a_symbol() {
+        assert_eq!(Symbol::from_code(255), None);
+    }
+
+    #[test]
+    fn test_beyond_a_byte() {
+        assert_eq!(Symbol::from_code(300).unwrap().code(), 300);
+    }
+
+    #[test]
+    fn test_every_code_round_trips() {
+        for code in 0..=254 {
+            if let Some(symbol) = Symbol::from_code(code) {
+                assert_eq!(symbol.code(), code);
+            }
+        }
+        for terminal in Terminal::ALL {
+            assert_eq!(Terminal::from_name(terminal.name()), Some(terminal));
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(NonTerminal::SCALA.to_string(), "<scala> (42)");
+        assert_eq!(Terminal::Semicolon.to_string(), "; (31)");
+        assert_eq!(Symbol::Bottom.to_string(), "z0 (0)");
+        assert_eq!(NonTerminal::from_name("<bool>"), Some(NonTerminal::BOOL));
+    }
+
+    #[test]
+    fn test_name_in() {
+        let mut names = std::collections::BTreeMap::new();
+        names.insert(NonTerminal::new(70).unwrap(), "<list>".to_string());
+
+        assert_eq!(
+            Symbol::NonTerminal(NonTerminal::new(70).unwrap()).name_in(&names),
+            "<list>"
+        );
+        assert_eq!(
+            Symbol::NonTerminal(NonTerminal::SCALA).name_in(&names),
+            "<scala>"
+        );
+        assert_eq!(Symbol::Terminal(Terminal::Semicolon).name_in(&names), ";");
+    }
+}
@@ -0,0 +1,451 @@
+#![warn(clippy::all)]
+
+// Observers for the steps of the PDA. The parser hands every step to a `Trace`, which can print it, record it or ignore it.
+// `TableTrace` prints the step table that this program has always printed. The others are for other programs to read, or for when nothing should be printed at all.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crate::bookkeeper::Token;
+use crate::symbol::{NonTerminal, Symbol};
+use crate::tree::json_string;
+
+// What the PDA did in a step.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    PushStart,           // Push the start symbol onto the empty stack.
+    Resume(NonTerminal), // Pick the parsing back up from the nonterminal, after the stack was emptied by an error.
+    UseRule(u16),        // Replace the nonterminal on top of the stack with the body of the rule.
+    Match,               // Match the terminal on top of the stack with the lookahead.
+    ErrorPopStackTop,    // Panic mode: give up on the stack top.
+    ErrorSkipLookahead,  // Panic mode: skip the lookahead.
+}
+
+// A step of the PDA.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub(crate) number: u32,
+    pub(crate) stack_top: Symbol, // The symbol that was popped, or the bottom of the stack if nothing was.
+    pub(crate) lookahead: Option<Token>,
+    pub(crate) action: Action,
+    pub(crate) stack: Vec<Symbol>, // The stack after the step, from the bottom up.
+    pub(crate) names: Arc<BTreeMap<NonTerminal, String>>, // The names of any nonterminals that are not in the built-in grammar.
+}
+
+impl Step {
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn stack_top(&self) -> Symbol {
+        self.stack_top
+    }
+
+    // The lookahead token. There is none when the start symbol is pushed or the parsing is picked back up.
+    pub fn lookahead(&self) -> Option<&Token> {
+        self.lookahead.as_ref()
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    // The rule that was used, if any.
+    pub fn rule(&self) -> Option<u16> {
+        match self.action {
+            Action::UseRule(rule) => Some(rule),
+            _ => None,
+        }
+    }
+
+    pub fn stack(&self) -> &[Symbol] {
+        &self.stack
+    }
+
+    // The name of a symbol, including the names of the grammar's own nonterminals.
+    pub fn symbol_name(&self, symbol: Symbol) -> String {
+        symbol.name_in(&self.names)
+    }
+
+    // The action, as it is written in the step table.
+    pub fn action_to_string(&self) -> String {
+        match self.action {
+            Action::PushStart => "Push start symbol.".to_string(),
+            Action::Resume(nonterminal) => format!(
+                "Resume at {} ({}).",
+                self.symbol_name(nonterminal.into()),
+                nonterminal.code()
+            ),
+            Action::UseRule(rule) => format!("Use rule {}.", rule),
+            Action::Match => "Match.".to_string(),
+            Action::ErrorPopStackTop => "ERROR, pop stack top.".to_string(),
+            Action::ErrorSkipLookahead => "ERROR, skip lookahead.".to_string(),
+        }
+    }
+
+    // A short name for the action, for the formats that are meant for other programs to read.
+    fn action_kind(&self) -> &'static str {
+        match self.action {
+            Action::PushStart => "push-start",
+            Action::Resume(_) => "resume",
+            Action::UseRule(_) => "use-rule",
+            Action::Match => "match",
+            Action::ErrorPopStackTop => "error-pop-stack-top",
+            Action::ErrorSkipLookahead => "error-skip-lookahead",
+        }
+    }
+}
+
+// Something that is told about every step of the parsing.
+pub trait Trace {
+    // The parsing is about to start.
+    fn begin(&mut self) {}
+
+    fn step(&mut self, step: &Step);
+
+    // The parsing is over, and the source was accepted or rejected.
+    fn end(&mut self, _accepted: bool) {}
+
+    // The first error that the trace ran into while writing, if there was one. A write that fails does not stop the parsing, but nothing more is written after it.
+    fn take_error(&mut self) -> Option<io::Error> {
+        None
+    }
+}
+
+// Ignore every step.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentTrace;
+
+impl Trace for SilentTrace {
+    fn step(&mut self, _step: &Step) {}
+}
+
+// Keep every step, so that it can be looked at afterwards.
+impl Trace for Vec<Step> {
+    fn step(&mut self, step: &Step) {
+        self.push(step.clone());
+    }
+}
+
+// Where the traces that print write to. It keeps the first error that writing runs into, and writes nothing after it.
+struct Sink<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Sink<W> {
+    fn new(out: W) -> Self {
+        Sink { out, error: None }
+    }
+
+    fn line(&mut self, args: fmt::Arguments) {
+        if self.error.is_none() {
+            if let Err(why) = writeln!(self.out, "{}", args) {
+                self.error = Some(why);
+            }
+        }
+    }
+}
+
+// Print the step table: a header, one row per step, and ACCEPT or REJECT at the end.
+pub struct TableTrace<W: Write> {
+    out: Sink<W>,
+}
+
+impl<W: Write> TableTrace<W> {
+    pub fn new(out: W) -> Self {
+        TableTrace {
+            out: Sink::new(out),
+        }
+    }
+}
+
+impl<W: Write> Trace for TableTrace<W> {
+    fn begin(&mut self) {
+        // Print out the table header for the parse output
+        self.out.line(format_args!(
+            "{0: <30} | {1: <30} | {2: <30} | {3: <}",
+            "Steps", "Stack Top", "Lookahead", "Action"
+        ));
+    }
+
+    // Print the parse output, with the following format:
+    // (Steps, stack top, lookahead, action)
+    fn step(&mut self, step: &Step) {
+        let lookahead = match &step.lookahead {
+            Some(tkn) => format!("{} ({})", tkn.token, tkn.terminal.code()),
+            None => "None".to_string(),
+        };
+        self.out.line(format_args!(
+            "{0: <30} | {1: <30} | {2: <30} | {3: <}",
+            step.number.to_string(),
+            format!(
+                "{} ({})",
+                step.symbol_name(step.stack_top),
+                step.stack_top.code()
+            ),
+            lookahead,
+            step.action_to_string()
+        ));
+    }
+
+    fn end(&mut self, accepted: bool) {
+        if accepted {
+            self.out.line(format_args!("ACCEPT"));
+        } else {
+            self.out.line(format_args!("REJECT"));
+        }
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.out.error.take()
+    }
+}
+
+// Print one JSON object per step, and one at the end with whether the source was accepted.
+pub struct JsonLinesTrace<W: Write> {
+    out: Sink<W>,
+}
+
+impl<W: Write> JsonLinesTrace<W> {
+    pub fn new(out: W) -> Self {
+        JsonLinesTrace {
+            out: Sink::new(out),
+        }
+    }
+}
+
+impl<W: Write> Trace for JsonLinesTrace<W> {
+    fn step(&mut self, step: &Step) {
+        let lookahead = match &step.lookahead {
+            Some(tkn) => format!(
+                "{{\"token\":{},\"code\":{},\"line\":{},\"column\":{}}}",
+                json_string(&tkn.token),
+                tkn.terminal.code(),
+                tkn.line_number,
+                tkn.column
+            ),
+            None => "null".to_string(),
+        };
+        let rule = match step.rule() {
+            Some(rule) => rule.to_string(),
+            None => "null".to_string(),
+        };
+        let stack: Vec<String> = step
+            .stack
+            .iter()
+            .map(|symbol| json_string(&step.symbol_name(*symbol)))
+            .collect();
+        self.out.line(format_args!(
+            "{{\"step\":{},\"stack_top\":{},\"stack_top_code\":{},\"lookahead\":{},\"action\":{},\"rule\":{},\"stack\":[{}]}}",
+            step.number,
+            json_string(&step.symbol_name(step.stack_top)),
+            step.stack_top.code(),
+            lookahead,
+            json_string(step.action_kind()),
+            rule,
+            stack.join(",")
+        ));
+    }
+
+    fn end(&mut self, accepted: bool) {
+        self.out.line(format_args!("{{\"accepted\":{}}}", accepted));
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.out.error.take()
+    }
+}
+
+// Print the steps as CSV, with a header row. The stack is written as its symbols separated by spaces, from the bottom up.
+pub struct CsvTrace<W: Write> {
+    out: Sink<W>,
+}
+
+impl<W: Write> CsvTrace<W> {
+    pub fn new(out: W) -> Self {
+        CsvTrace {
+            out: Sink::new(out),
+        }
+    }
+}
+
+impl<W: Write> Trace for CsvTrace<W> {
+    fn begin(&mut self) {
+        self.out.line(format_args!(
+            "step,stack_top,stack_top_code,lookahead,lookahead_code,action,rule,stack"
+        ));
+    }
+
+    fn step(&mut self, step: &Step) {
+        let (lookahead, lookahead_code) = match &step.lookahead {
+            Some(tkn) => (csv_field(&tkn.token), tkn.terminal.code().to_string()),
+            None => (String::new(), String::new()),
+        };
+        let stack: Vec<String> = step
+            .stack
+            .iter()
+            .map(|symbol| step.symbol_name(*symbol))
+            .collect();
+        self.out.line(format_args!(
+            "{},{},{},{},{},{},{},{}",
+            step.number,
+            csv_field(&step.symbol_name(step.stack_top)),
+            step.stack_top.code(),
+            lookahead,
+            lookahead_code,
+            step.action_kind(),
+            step.rule().map_or(String::new(), |rule| rule.to_string()),
+            csv_field(&stack.join(" "))
+        ));
+    }
+
+    fn take_error(&mut self) -> Option<io::Error> {
+        self.out.error.take()
+    }
+}
+
+// Quote a CSV field if it needs it.
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod trace_tests {
+    use crate::bookkeeper::Bookkeeper;
+    use crate::parser::Parser;
+    use crate::symbol::Terminal;
+    use crate::trace::*;
+
+    // Parse the source with the trace, and return whether it was accepted.
+    fn run<T: Trace>(source: &str, trace: &mut T) -> bool {
+        let mut parser = Parser::new(source.to_string(), Bookkeeper::new());
+
+        parser.parse_with(trace)
+    }
+
+    #[test]
+    fn test_recorded_steps() {
+        let mut steps: Vec<Step> = Vec::new();
+        assert!(run("package a;\n$\n", &mut steps));
+
+        assert_eq!(steps[0].action(), Action::PushStart);
+        assert!(steps[0].lookahead().is_none());
+        assert_eq!(
+            steps[0].stack(),
+            &[Symbol::Bottom, NonTerminal::SCALA.into()]
+        );
+        assert_eq!(steps[1].rule(), Some(1));
+        assert_eq!(steps[1].stack_top(), NonTerminal::SCALA.into());
+        assert_eq!(steps[1].stack().len(), 4);
+        let matched: Vec<u32> = steps
+            .iter()
+            .filter(|step| step.action() == Action::Match)
+            .map(|step| step.number())
+            .collect();
+        assert_eq!(matched, vec![4, 5, 6]);
+        assert_eq!(steps[3].lookahead().unwrap().terminal(), Terminal::Package);
+        assert_eq!(steps.last().unwrap().stack(), &[Symbol::Bottom]);
+    }
+
+    #[test]
+    fn test_table() {
+        let mut out: Vec<u8> = Vec::new();
+        run("package a;\n$\n", &mut TableTrace::new(&mut out));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert!(lines[0].starts_with("Steps "));
+        assert_eq!(
+            lines[1],
+            format!(
+                "{: <30} | {: <30} | {: <30} | Push start symbol.",
+                1, "z0 (0)", "None"
+            )
+        );
+        assert_eq!(
+            lines[4],
+            format!(
+                "{: <30} | {: <30} | {: <30} | Match.",
+                4, "package (3)", "package (3)"
+            )
+        );
+        assert_eq!(lines.last(), Some(&"ACCEPT"));
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let mut out: Vec<u8> = Vec::new();
+        run("package a\n$\n", &mut JsonLinesTrace::new(&mut out));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "{\"step\":1,\"stack_top\":\"z0\",\"stack_top_code\":0,\"lookahead\":null,\"action\":\"push-start\",\"rule\":null,\"stack\":[\"z0\",\"<scala>\"]}"
+        );
+        assert!(lines[1]
+            .contains("\"lookahead\":{\"token\":\"package\",\"code\":3,\"line\":1,\"column\":1}"));
+        assert!(lines[1].contains("\"action\":\"use-rule\",\"rule\":1"));
+        assert!(out.contains("\"action\":\"error-pop-stack-top\""));
+        assert_eq!(lines.last(), Some(&"{\"accepted\":false}"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut out: Vec<u8> = Vec::new();
+        run("package a;\n$\n", &mut CsvTrace::new(&mut out));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "step,stack_top,stack_top_code,lookahead,lookahead_code,action,rule,stack"
+        );
+        assert_eq!(lines[1], "1,z0,0,,,push-start,,z0 <scala>");
+        assert_eq!(
+            lines[2],
+            "2,<scala>,42,package,3,use-rule,1,z0 <scala-body> <imports> <packages>"
+        );
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("\"a\""), "\"\"\"a\"\"\"");
+    }
+
+    // A writer whose reader has gone away, like a closed pipe.
+    struct Full;
+
+    impl Write for Full {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_errors_are_kept() {
+        let mut trace = TableTrace::new(Full);
+        assert!(run("package a;\n$\n", &mut trace));
+
+        assert_eq!(
+            trace.take_error().map(|why| why.kind()),
+            Some(io::ErrorKind::BrokenPipe)
+        );
+        assert!(trace.take_error().is_none());
+        assert!(CsvTrace::new(Full).take_error().is_none());
+        assert!(SilentTrace.take_error().is_none());
+    }
+
+    #[test]
+    fn test_silent() {
+        assert!(run("package a;\n$\n", &mut SilentTrace));
+    }
+}
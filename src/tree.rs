@@ -0,0 +1,265 @@
+#![warn(clippy::all)]
+
+// The concrete parse tree, which the PDA records as it goes: every expanded nonterminal has the symbols of its rule as children, and every matched terminal has its token.
+// An epsilon rule gets a single `epsilon` leaf, so that it still shows up in the tree.
+// If the parsing has to be picked back up after an error, it starts a new tree, so there may be more than one root. A nonterminal that was given up on in panic mode has no children, and a terminal that was missing has no token.
+
+use crate::bookkeeper::Token;
+use crate::dot::quote;
+use crate::symbol::{Symbol, Terminal};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub(crate) symbol: Symbol,
+    pub(crate) name: String, // The name of the symbol, which may come from a grammar file.
+    pub(crate) rule: Option<u16>, // The rule that a nonterminal was expanded with.
+    pub(crate) token: Option<Token>, // The token that a terminal was matched with.
+    pub(crate) children: Vec<usize>,
+}
+
+impl Node {
+    pub fn symbol(&self) -> Symbol {
+        self.symbol
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn rule(&self) -> Option<u16> {
+        self.rule
+    }
+
+    pub fn token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    fn is_epsilon(&self) -> bool {
+        self.symbol == Symbol::Terminal(Terminal::EndOfInput)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseTree {
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) roots: Vec<usize>,
+}
+
+impl ParseTree {
+    pub fn new() -> Self {
+        ParseTree::default()
+    }
+
+    // Every node in the tree. Nodes refer to their children by their index here.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    // The node that the parsing started from, followed by any that it was picked back up from.
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Add a node with no children, and return its index.
+    pub(crate) fn add(&mut self, symbol: Symbol, name: String) -> usize {
+        self.nodes.push(Node {
+            symbol,
+            name,
+            rule: None,
+            token: None,
+            children: Vec::new(),
+        });
+
+        self.nodes.len() - 1
+    }
+
+    pub(crate) fn add_root(&mut self, symbol: Symbol, name: String) -> usize {
+        let root = self.add(symbol, name);
+        self.roots.push(root);
+
+        root
+    }
+
+    // Print the tree with one node per line, indented by its depth.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for root in &self.roots {
+            self.node_to_text(*root, 0, &mut text);
+        }
+
+        text
+    }
+
+    fn node_to_text(&self, index: usize, depth: usize, text: &mut String) {
+        let node = &self.nodes[index];
+        text.push_str(&"  ".repeat(depth));
+        text.push_str(&node.name);
+        if let Some(rule) = node.rule {
+            text.push_str(&format!(" (rule {})", rule));
+        }
+        if let Some(tkn) = &node.token {
+            text.push_str(&format!(" `{}` (line {})", tkn.token, tkn.line_number));
+        }
+        text.push('\n');
+        for child in &node.children {
+            self.node_to_text(*child, depth + 1, text);
+        }
+    }
+
+    // Print the tree as JSON: a list of the roots, with each node as an object.
+    pub fn to_json(&self) -> String {
+        let roots: Vec<String> = self
+            .roots
+            .iter()
+            .map(|root| self.node_to_json(*root))
+            .collect();
+
+        format!("[{}]", roots.join(","))
+    }
+
+    fn node_to_json(&self, index: usize) -> String {
+        let node = &self.nodes[index];
+        let mut fields = vec![
+            format!("\"symbol\":{}", json_string(&node.name)),
+            format!("\"code\":{}", node.symbol.code()),
+        ];
+        if let Some(rule) = node.rule {
+            fields.push(format!("\"rule\":{}", rule));
+        }
+        if let Some(tkn) = &node.token {
+            fields.push(format!("\"token\":{}", json_string(&tkn.token)));
+            fields.push(format!("\"line\":{}", tkn.line_number));
+            fields.push(format!("\"column\":{}", tkn.column));
+        }
+        if let Symbol::NonTerminal(_) = node.symbol {
+            let children: Vec<String> = node
+                .children
+                .iter()
+                .map(|child| self.node_to_json(*child))
+                .collect();
+            fields.push(format!("\"children\":[{}]", children.join(",")));
+        }
+
+        format!("{{{}}}", fields.join(","))
+    }
+
+    // Print the tree as a Graphviz DOT graph. Terminals are drawn as boxes, and epsilon leaves as plain text.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tree {\n    node [shape=ellipse];\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            let label = match &node.token {
+                Some(tkn) if tkn.token != node.name => format!("{}\n{}", node.name, tkn.token),
+                _ => node.name.clone(),
+            };
+            let shape = match node.symbol {
+                Symbol::NonTerminal(_) => "",
+                _ if node.is_epsilon() => ", shape=plaintext",
+                _ => ", shape=box",
+            };
+            dot += &format!("    n{} [label={}{}];\n", index, quote(&label), shape);
+            for child in &node.children {
+                dot += &format!("    n{} -> n{};\n", index, child);
+            }
+        }
+        dot += "}\n";
+
+        dot
+    }
+}
+
+// Quote a string for JSON.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\t' => quoted += "\\t",
+            '\r' => quoted += "\\r",
+            c if (c as u32) < 0x20 => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tree_tests {
+    use crate::bookkeeper::Bookkeeper;
+    use crate::parser::Parser;
+    use crate::trace::SilentTrace;
+    use crate::tree::*;
+
+    fn tree(source: &str) -> ParseTree {
+        let mut parser = Parser::new(source.to_string(), Bookkeeper::new());
+        parser.parse_with(&mut SilentTrace);
+
+        parser.tree().clone()
+    }
+
+    #[test]
+    fn test_text() {
+        let expected = "\
+<scala> (rule 1)
+  <packages> (rule 2)
+    package `package` (line 1)
+    [id] `a` (line 1)
+    ; `;` (line 1)
+    <packages> (rule 3)
+      epsilon
+  <imports> (rule 5)
+    epsilon
+  <scala-body> (rule 7)
+    epsilon
+";
+
+        assert_eq!(tree("package a;\n$\n").to_text(), expected);
+    }
+
+    #[test]
+    fn test_json() {
+        let json = tree("package a;\n$\n").to_json();
+
+        assert!(json.starts_with("[{\"symbol\":\"<scala>\",\"code\":42,\"rule\":1,\"children\":[{\"symbol\":\"<packages>\""));
+        assert!(json
+            .contains("{\"symbol\":\"[id]\",\"code\":1,\"token\":\"a\",\"line\":1,\"column\":9}"));
+        assert!(json.contains("{\"symbol\":\"epsilon\",\"code\":253}"));
+        assert_eq!(json_string("a\"b\\\n\u{1}"), "\"a\\\"b\\\\\\n\\u0001\"");
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = tree("package a;\n$\n").to_dot();
+
+        assert!(dot.starts_with("digraph tree {\n"));
+        assert!(dot.contains("    n0 [label=\"<scala>\"];\n    n0 -> n1;\n"));
+        assert!(dot.contains("[label=\"[id]\\na\", shape=box];"));
+        assert!(dot.contains("[label=\"epsilon\", shape=plaintext];"));
+    }
+
+    #[test]
+    fn test_errors_start_new_trees() {
+        // The missing `;` is left without a token, and the stray `}` makes the parsing pick back up at the next body.
+        let tree = tree("package a\n}\nabstract class {\n}\n$\n");
+
+        assert_eq!(tree.roots().len(), 2);
+        let semicolon = tree
+            .nodes()
+            .iter()
+            .find(|node| node.symbol() == Symbol::Terminal(Terminal::Semicolon))
+            .unwrap();
+        assert!(semicolon.token().is_none());
+        assert_eq!(tree.nodes()[tree.roots()[1]].name(), "<scala-body>");
+    }
+}